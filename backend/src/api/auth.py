@@ -0,0 +1,81 @@
+"""Per-launch token authentication for the REST and WebSocket API.
+
+The desktop app starts the backend with a random token in
+``OPENWHISPER_TOKEN``; every request must then carry it, so other local
+programs cannot read sessions or start recording. A backend started on its
+own (``npm run backend``, for the web frontend) has no token and stays open.
+
+The token never goes in a URL, where the access log would print it.
+"""
+
+import hmac
+
+from starlette.responses import JSONResponse
+from starlette.types import ASGIApp, Message, Receive, Scope, Send
+
+TOKEN_ENV = "OPENWHISPER_TOKEN"
+PORT_ENV = "OPENWHISPER_PORT"
+
+# Browsers cannot set headers on WebSocket connections, but they can offer
+# subprotocols: the client offers WS_PROTOCOL and WS_TOKEN_PREFIX + token,
+# and the server picks WS_PROTOCOL.
+WS_PROTOCOL = "openwhisper"
+WS_TOKEN_PREFIX = "openwhisper.token."
+
+
+def _request_token(scope: Scope) -> str | None:
+    """Bearer token from the headers, or from the offered WebSocket
+    subprotocols."""
+    for name, value in scope.get("headers", []):
+        if name == b"authorization":
+            scheme, _, token = value.decode("latin-1").partition(" ")
+            if scheme.lower() == "bearer":
+                return token.strip()
+    for protocol in scope.get("subprotocols", []):
+        if protocol.startswith(WS_TOKEN_PREFIX):
+            return protocol.removeprefix(WS_TOKEN_PREFIX)
+    return None
+
+
+def _selecting_protocol(send: Send) -> Send:
+    """Wrap *send* so the WebSocket handshake answers with WS_PROTOCOL, which
+    browsers require when subprotocols were offered."""
+
+    async def wrapped(message: Message):
+        if message["type"] == "websocket.accept" and not message.get("subprotocol"):
+            message = {**message, "subprotocol": WS_PROTOCOL}
+        await send(message)
+
+    return wrapped
+
+
+class TokenAuthMiddleware:
+    """Reject HTTP requests (401) and WebSocket handshakes (403) without the
+    expected token. CORS preflight requests carry no credentials and pass."""
+
+    def __init__(self, app: ASGIApp, token: str | None = None):
+        self.app = app
+        self.token = token or None
+
+    async def __call__(self, scope: Scope, receive: Receive, send: Send):
+        if (
+            self.token is None
+            or scope["type"] not in ("http", "websocket")
+            or (scope["type"] == "http" and scope["method"] == "OPTIONS")
+        ):
+            await self.app(scope, receive, send)
+            return
+
+        token = _request_token(scope)
+        if token is not None and hmac.compare_digest(token, self.token):
+            if scope["type"] == "websocket":
+                send = _selecting_protocol(send)
+            await self.app(scope, receive, send)
+            return
+
+        if scope["type"] == "websocket":
+            # Closing before accepting makes the server refuse the handshake
+            await send({"type": "websocket.close", "code": 1008})
+            return
+        response = JSONResponse({"detail": "Missing or invalid token"}, status_code=401)
+        await response(scope, receive, send)
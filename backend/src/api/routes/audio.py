@@ -12,10 +12,13 @@ router = APIRouter()
 
 
 @router.get("/api/audio/devices")
-async def list_audio_devices():
-    """List available audio input devices."""
+async def list_audio_devices(rescan: bool = False):
+    """List available audio input devices.
+
+    ``rescan=true`` looks for devices plugged in since the backend started.
+    """
     try:
-        devices = AudioCapture.list_devices()
+        devices = AudioCapture.list_devices(rescan=rescan)
     except Exception as exc:
         logger.error("Failed to list audio devices: %s", exc)
         raise HTTPException(
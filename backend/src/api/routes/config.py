@@ -5,7 +5,13 @@ import logging
 import yaml
 from fastapi import APIRouter, HTTPException, Request
 
-from src.config import AppConfig, get_config_path
+from src.config import (
+    AUTO_DETECT,
+    LANGUAGE_NAMES,
+    SUPPORTED_LANGUAGES,
+    AppConfig,
+    get_config_path,
+)
 
 logger = logging.getLogger(__name__)
 
@@ -23,6 +29,8 @@ _HOT_RELOAD_PREFIXES = (
     "models.transcription.compute_type",
     "models.llm",
     "search.distance_threshold",
+    "injection",
+    "shortcuts",
 )
 # Fields that require an application restart
 _RESTART_REQUIRED_PREFIXES = (
@@ -68,6 +76,23 @@ async def get_config():
     return config.model_dump()
 
 
+@router.get("/api/languages")
+async def get_languages():
+    """Return the languages sessions can use and the configured default."""
+    from src.main import config
+
+    if config is None:
+        raise HTTPException(status_code=503, detail="Configuration not loaded")
+    return {
+        "languages": [
+            {"code": code, "name": LANGUAGE_NAMES.get(code, code)}
+            for code in SUPPORTED_LANGUAGES
+        ],
+        "auto_detect": AUTO_DETECT,
+        "default": config.language,
+    }
+
+
 @router.put("/api/config")
 async def update_config(request: Request):
     """Validate, persist, and hot-reload configuration changes.
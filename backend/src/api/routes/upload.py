@@ -27,10 +27,10 @@ async def upload_file_for_transcription(
     for real-time transcription progress.
     """
     from src.main import config as app_config
-    from src.config import SUPPORTED_LANGUAGES
+    from src.config import SUPPORTED_LANGUAGES, is_valid_language
 
     # Validate language
-    if language not in SUPPORTED_LANGUAGES:
+    if not is_valid_language(language):
         raise HTTPException(
             status_code=400,
             detail=f"Unsupported language: {language}. Supported: {', '.join(SUPPORTED_LANGUAGES)}",
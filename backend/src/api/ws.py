@@ -117,12 +117,12 @@ async def _handle_transcription_session(
 ):
     """Orchestrate a single transcription session."""
     from src.main import config
-    from src.config import SUPPORTED_LANGUAGES
+    from src.config import is_valid_language, model_language
 
     _debug(f"[WS] _handle_transcription_session: mode={mode}, language_override={language_override}")
 
     language = language_override or config.language
-    if language not in SUPPORTED_LANGUAGES:
+    if not is_valid_language(language):
         logger.warning("Unsupported language %r requested, falling back to config default", language)
         language = config.language
 
@@ -234,7 +234,7 @@ async def _handle_transcription_session(
             "state": "loading_model",
         }))
 
-        async with whisper_client.connect(language=language) as session:
+        async with whisper_client.connect(language=model_language(language)) as session:
             _debug(f"[WS] Model loaded, sending recording status (device={session.actual_device})")
             logger.info("[WS] Model loaded, sending recording status (device=%s)", session.actual_device)
             await websocket.send_text(json.dumps({
@@ -422,6 +422,7 @@ async def websocket_transcribe_file(websocket: WebSocket, session_id: int):
 
 async def _handle_file_transcription(websocket: WebSocket, pending):
     """Orchestrate file transcription with progress streaming."""
+    from src.config import model_language
     from src.main import config
     from src.transcription.file_transcriber import transcribe_file_streaming
 
@@ -467,7 +468,7 @@ async def _handle_file_transcription(websocket: WebSocket, pending):
         audio_duration_s, segments = await transcribe_file_streaming(
             file_path=pending.file_path,
             config=config.models.transcription,
-            language=language,
+            language=model_language(language),
         )
 
         # Send transcribing status + file info
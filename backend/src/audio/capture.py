@@ -8,6 +8,7 @@ transcription engine.
 import asyncio
 import base64
 import logging
+import threading
 
 import numpy as np
 import sounddevice as sd
@@ -28,6 +29,13 @@ class AudioCapture:
         # Call capture.stop() to stop recording
     """
 
+    # Streams currently open across all instances; PortAudio can only be
+    # re-initialized (to pick up newly plugged-in devices) when there are none.
+    # Guarded by _portaudio_lock, which is also held while re-initializing so a
+    # stream cannot open halfway through.
+    _open_streams = 0
+    _portaudio_lock = threading.Lock()
+
     def __init__(
         self,
         sample_rate: int = 16000,
@@ -38,7 +46,8 @@ class AudioCapture:
         self.sample_rate = sample_rate
         self.channels = channels
         self.chunk_duration_ms = chunk_duration_ms
-        # Resolve device: "default"/None → None, numeric string → int index, else pass as-is
+        # Resolve device: "default"/None → None, numeric string → int index,
+        # else a device name, looked up when the stream opens
         if device is None or device == "default":
             self.device = None
         elif isinstance(device, str) and device.isdigit():
@@ -73,15 +82,23 @@ class AudioCapture:
         self._running = True
 
         try:
-            self._stream = sd.InputStream(
-                samplerate=self.sample_rate,
-                channels=self.channels,
-                dtype="float32",
-                blocksize=self.chunk_samples,
-                device=self.device,
-                callback=self._audio_callback,
-            )
-            self._stream.start()
+            with AudioCapture._portaudio_lock:
+                self._stream = sd.InputStream(
+                    samplerate=self.sample_rate,
+                    channels=self.channels,
+                    dtype="float32",
+                    blocksize=self.chunk_samples,
+                    device=self._resolve_device(),
+                    callback=self._audio_callback,
+                )
+                self._stream.start()
+                AudioCapture._open_streams += 1
+        except ValueError as e:
+            # sounddevice found no device matching the configured name
+            raise AudioDeviceNotFoundError(
+                f"Audio input device not found (check that a microphone "
+                f"is connected): {e}"
+            ) from e
         except sd.PortAudioError as e:
             error_msg = str(e).lower()
             if (
@@ -119,14 +136,44 @@ class AudioCapture:
     def _stop_stream(self):
         """Clean up the sounddevice stream."""
         if self._stream is not None:
-            self._stream.stop()
-            self._stream.close()
-            self._stream = None
+            with AudioCapture._portaudio_lock:
+                self._stream.stop()
+                self._stream.close()
+                self._stream = None
+                AudioCapture._open_streams -= 1
             logger.info("Audio capture stopped")
 
+    def _resolve_device(self) -> int | str | None:
+        """Index of the configured device as PortAudio currently numbers it.
+
+        Indices shift when devices come and go, so a device is configured by
+        name and looked up here; a name listed under several host APIs gets
+        the entry :meth:`list_devices` would show. A name matching no entry
+        exactly is left for sounddevice to match as a substring.
+        """
+        if not isinstance(self.device, str):
+            return self.device
+        for entry in AudioCapture._input_devices():
+            if entry["name"] == self.device:
+                return entry["index"]
+        return self.device
+
+    @staticmethod
+    def list_devices(rescan: bool = False) -> list[dict]:
+        """List available audio input devices (deduplicated by name).
+
+        PortAudio enumerates devices once, when it is initialized. With
+        *rescan*, it is re-initialized first so devices plugged in since show
+        up, unless a stream is open (which re-initializing would break).
+        """
+        with AudioCapture._portaudio_lock:
+            if rescan and AudioCapture._open_streams == 0:
+                sd._terminate()
+                sd._initialize()
+            return AudioCapture._input_devices()
+
     @staticmethod
-    def list_devices() -> list[dict]:
-        """List available audio input devices (deduplicated by name)."""
+    def _input_devices() -> list[dict]:
         devices = sd.query_devices()
         seen: dict[str, dict] = {}
         for i, dev in enumerate(devices):
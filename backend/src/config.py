@@ -4,6 +4,7 @@ Loads config.yaml from the project root and validates all fields
 with sensible defaults. Falls back to defaults if config.yaml is missing.
 """
 
+import json
 import logging
 from pathlib import Path
 from typing import Literal
@@ -18,10 +19,81 @@ SUPPORTED_LANGUAGES = (
     "fr", "en", "es", "pt", "hi", "de", "nl", "it", "ar", "ru", "zh", "ja", "ko"
 )
 
+# Language value that lets the model detect the spoken language
+AUTO_DETECT = "auto"
+
+# Native names, for language pickers (tray, settings)
+LANGUAGE_NAMES: dict[str, str] = {
+    "fr": "Français", "en": "English", "es": "Español", "pt": "Português",
+    "hi": "हिन्दी", "de": "Deutsch", "nl": "Nederlands", "it": "Italiano",
+    "ar": "العربية", "ru": "Русский", "zh": "中文", "ja": "日本語", "ko": "한국어",
+}
+
+
+def is_valid_language(language: str) -> bool:
+    """Whether *language* can be requested for a session."""
+    return language == AUTO_DETECT or language in SUPPORTED_LANGUAGES
+
+
+def model_language(language: str) -> str | None:
+    """Language to pass to the model; ``None`` makes it detect the language."""
+    return None if language == AUTO_DETECT else language
+
+
+# Apps dictation never types into by default; the desktop app embeds the
+# same file for when it runs without a config.yaml
+DEFAULT_DENY_LIST: list[str] = json.loads(
+    (Path(__file__).parent / "default_deny_list.json").read_text(encoding="utf-8")
+)
+
 
 class ShortcutsConfig(BaseModel):
     toggle_dictation: str = "Ctrl+Shift+D"
     toggle_transcription: str = "Ctrl+Shift+T"
+    # Optional actions, unbound unless set
+    undo_dictation: str = ""
+    cancel_session: str = ""
+    cycle_language: str = ""
+    toggle_overlay: str = ""
+    open_main_window: str = ""
+    paste_last_transcript: str = ""
+    pause_transcription: str = ""
+    language_dictation: dict[str, str] = Field(
+        default_factory=dict,
+        description="Language code -> shortcut that starts dictation in that language",
+    )
+    dictation_mode: Literal["toggle", "push_to_talk"] = Field(
+        default="toggle",
+        description="toggle = press to start and again to stop; push_to_talk = dictate while held",
+    )
+    push_to_talk_min_hold_ms: int = Field(
+        default=250,
+        ge=0,
+        le=2000,
+        description="Push-to-talk presses shorter than this are ignored",
+    )
+    double_tap_modifier: (
+        Literal[
+            "left_ctrl",
+            "right_ctrl",
+            "left_alt",
+            "right_alt",
+            "left_shift",
+            "right_shift",
+            "left_super",
+            "right_super",
+        ]
+        | None
+    ) = Field(
+        default=None,
+        description="Modifier to double-tap to toggle dictation (off when unset)",
+    )
+    double_tap_interval_ms: int = Field(
+        default=300,
+        ge=100,
+        le=1000,
+        description="Longest tap, and longest gap between the two taps",
+    )
 
 
 class TranscriptionModelConfig(BaseModel):
@@ -152,7 +224,15 @@ class StorageConfig(BaseModel):
 
 class BackendConfig(BaseModel):
     host: str = "127.0.0.1"
+    # Overridden by OPENWHISPER_PORT when the desktop app starts the backend
     port: int = Field(default=8001, ge=1, le=65535)
+    # Whether the desktop app starts (and restarts) the backend itself
+    managed: bool = True
+    # Command the desktop app runs from the backend directory
+    command: list[str] = Field(
+        default_factory=lambda: ["uv", "run", "python", "-m", "src.main"],
+        min_length=1,
+    )
 
 
 class SearchConfig(BaseModel):
@@ -168,6 +248,29 @@ class SearchConfig(BaseModel):
     )
 
 
+class InjectionConfig(BaseModel):
+    backend: Literal["auto", "clipboard", "unicode", "xtest", "wayland"] = Field(
+        default="auto",
+        description="How the desktop app types dictated text (auto = Wayland helpers on Wayland, clipboard paste elsewhere)",
+    )
+    focus_lock: Literal["off", "refocus", "pause"] = Field(
+        default="pause",
+        description="When the window dictation started in loses focus: type anyway, refocus it, or hold text until it is back",
+    )
+    deny_list: list[str] = Field(
+        default_factory=lambda: list(DEFAULT_DENY_LIST),
+        description="Apps (window class or process name, trailing * = prefix) dictation never types into",
+    )
+    allow_list: list[str] = Field(
+        default=[],
+        description="If non-empty, dictation only types into these apps",
+    )
+    blocked_action: Literal["clipboard", "drop"] = Field(
+        default="clipboard",
+        description="What happens to text meant for a blocked app: copy it to the clipboard or discard it",
+    )
+
+
 class AppConfig(BaseModel):
     language: str = Field(default="en", description="Default transcription language")
     max_upload_size_mb: int = Field(
@@ -177,6 +280,7 @@ class AppConfig(BaseModel):
         description="Maximum file upload size in MB (50-1024)",
     )
     shortcuts: ShortcutsConfig = ShortcutsConfig()
+    injection: InjectionConfig = InjectionConfig()
     models: ModelsConfig = ModelsConfig()
     audio: AudioConfig = AudioConfig()
     overlay: OverlayConfig = OverlayConfig()
@@ -71,7 +71,8 @@ _LANGUAGE_NAMES: dict[str, str] = {
     "fr": "French", "en": "English", "es": "Spanish", "de": "German",
     "it": "Italian", "pt": "Portuguese", "nl": "Dutch", "pl": "Polish",
     "ru": "Russian", "zh": "Chinese", "ja": "Japanese", "ko": "Korean",
-    "ar": "Arabic",
+    "ar": "Arabic", "hi": "Hindi",
+    "auto": "the same language as the text",
 }
 
 
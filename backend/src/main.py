@@ -1,6 +1,7 @@
 """FastAPI application entry point for OpenWhisper backend."""
 
 import logging
+import os
 import sys
 from contextlib import asynccontextmanager
 
@@ -9,6 +10,7 @@ from fastapi import FastAPI
 from fastapi.middleware.cors import CORSMiddleware
 
 from src.config import load_config, AppConfig
+from src.api.auth import PORT_ENV, TOKEN_ENV, TokenAuthMiddleware
 from src.api.routes import router as api_router
 from src.api.ws import ws_router
 from src.storage.database import init_db, close_db, set_db
@@ -54,7 +56,7 @@ async def lifespan(app: FastAPI):
         raise
 
     logger.info("Configuration loaded successfully")
-    logger.info(f"Backend running on {config.backend.host}:{config.backend.port}")
+    logger.info(f"Backend running on {config.backend.host}:{_port(config)}")
 
     try:
         db = await init_db(config.storage.db_path)
@@ -100,12 +102,15 @@ app = FastAPI(
     lifespan=lifespan,
 )
 
+# Added before CORS so that CORS wraps it and 401 responses keep their headers
+app.add_middleware(TokenAuthMiddleware, token=os.environ.get(TOKEN_ENV))
 app.add_middleware(
     CORSMiddleware,
     allow_origins=[
         "http://localhost:5173",
         "http://localhost:1420",
         "tauri://localhost",
+        "http://tauri.localhost",
     ],
     allow_credentials=True,
     allow_methods=["*"],
@@ -116,6 +121,11 @@ app.include_router(api_router)
 app.include_router(ws_router)
 
 
+def _port(cfg: AppConfig) -> int:
+    """Port picked by the desktop app, else the configured one."""
+    return int(os.environ.get(PORT_ENV) or cfg.backend.port)
+
+
 def main():
     """Run the backend server."""
     cfg = load_config()
@@ -123,7 +133,7 @@ def main():
     uvicorn.run(
         "src.main:app",
         host=cfg.backend.host,
-        port=cfg.backend.port,
+        port=_port(cfg),
         reload=False,  # Disabled: watchfiles reload kills WebSocket connections
         log_level="info",
     )
@@ -117,3 +117,49 @@ def test_list_devices_empty():
     ):
         devices = AudioCapture.list_devices()
         assert len(devices) == 0
+
+
+def test_list_devices_rescan():
+    """Test rescanning re-initializes PortAudio before listing."""
+    with patch("src.audio.capture.sd._terminate") as terminate, patch(
+        "src.audio.capture.sd._initialize"
+    ) as initialize, patch("src.audio.capture.sd.query_devices", return_value=[]):
+        AudioCapture.list_devices(rescan=True)
+        terminate.assert_called_once()
+        initialize.assert_called_once()
+
+
+def test_list_devices_rescan_skipped_while_streaming():
+    """Test rescanning leaves PortAudio alone while a stream is open."""
+    with patch.object(AudioCapture, "_open_streams", 1), patch(
+        "src.audio.capture.sd._terminate"
+    ) as terminate, patch("src.audio.capture.sd.query_devices", return_value=[]):
+        AudioCapture.list_devices(rescan=True)
+        terminate.assert_not_called()
+
+
+def test_device_name_resolves_to_current_index():
+    """Test a device configured by name opens at its index at stream time."""
+    capture = AudioCapture(device="USB Mic")
+    with patch(
+        "src.audio.capture.sd.query_devices",
+        return_value=[
+            {"name": "Built-in Mic", "max_input_channels": 2, "default_samplerate": 44100.0},
+            {"name": "USB Mic", "max_input_channels": 1, "default_samplerate": 48000.0},
+        ],
+    ):
+        assert capture._resolve_device() == 1
+
+
+@pytest.mark.asyncio
+async def test_stream_raises_on_unknown_device_name():
+    """Test that a device name matching nothing raises AudioDeviceNotFoundError."""
+    capture = AudioCapture(device="Gone Mic")
+    with patch("src.audio.capture.sd.query_devices", return_value=[]), patch(
+        "src.audio.capture.sd.InputStream",
+        side_effect=ValueError("No input device matching 'Gone Mic'"),
+    ):
+        with pytest.raises(AudioDeviceNotFoundError):
+            async for _ in capture.stream():
+                pass
+    assert AudioCapture._open_streams == 0
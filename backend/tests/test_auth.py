@@ -0,0 +1,74 @@
+"""Tests for the per-launch token middleware."""
+
+import pytest
+from fastapi import FastAPI, WebSocket
+from fastapi.testclient import TestClient
+from starlette.websockets import WebSocketDisconnect
+
+from src.api.auth import WS_PROTOCOL, WS_TOKEN_PREFIX, TokenAuthMiddleware
+
+TOKEN = "s3cret"
+
+
+def _client(token: str | None) -> TestClient:
+    app = FastAPI()
+    app.add_middleware(TokenAuthMiddleware, token=token)
+
+    @app.get("/ping")
+    async def ping():
+        return {"ok": True}
+
+    @app.websocket("/ws")
+    async def ws(websocket: WebSocket):
+        await websocket.accept()
+        await websocket.send_json({"ok": True})
+        await websocket.close()
+
+    return TestClient(app)
+
+
+def test_open_without_token():
+    """Without a configured token, requests need no credentials."""
+    assert _client(None).get("/ping").status_code == 200
+
+
+def test_rejects_missing_token():
+    resp = _client(TOKEN).get("/ping")
+    assert resp.status_code == 401
+
+
+def test_rejects_wrong_token():
+    resp = _client(TOKEN).get("/ping", headers={"Authorization": "Bearer nope"})
+    assert resp.status_code == 401
+
+
+def test_accepts_bearer_token():
+    resp = _client(TOKEN).get("/ping", headers={"Authorization": f"Bearer {TOKEN}"})
+    assert resp.status_code == 200
+
+
+def test_rejects_query_token():
+    """Tokens in URLs would end up in the access log."""
+    assert _client(TOKEN).get(f"/ping?token={TOKEN}").status_code == 401
+
+
+def test_lets_preflight_through():
+    """CORS preflight requests carry no credentials."""
+    resp = _client(TOKEN).options("/ping")
+    assert resp.status_code != 401
+
+
+def test_websocket_requires_token():
+    client = _client(TOKEN)
+    with pytest.raises(WebSocketDisconnect):
+        with client.websocket_connect("/ws") as ws:
+            ws.receive_json()
+
+    with pytest.raises(WebSocketDisconnect):
+        with client.websocket_connect(f"/ws?token={TOKEN}") as ws:
+            ws.receive_json()
+
+    protocols = [WS_PROTOCOL, WS_TOKEN_PREFIX + TOKEN]
+    with client.websocket_connect("/ws", subprotocols=protocols) as ws:
+        assert ws.accepted_subprotocol == WS_PROTOCOL
+        assert ws.receive_json() == {"ok": True}
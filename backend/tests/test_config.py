@@ -24,7 +24,22 @@ def test_config_defaults():
     assert config.models.transcription.beam_size == 5
     assert config.audio.chunk_duration_ms == 80
     assert config.backend.host == "127.0.0.1"
+    assert config.backend.managed is True
+    assert config.backend.command == ["uv", "run", "python", "-m", "src.main"]
     assert config.overlay.position == "top-right"
+    assert config.shortcuts.undo_dictation == ""
+    assert config.shortcuts.dictation_mode == "toggle"
+    assert config.shortcuts.cancel_session == ""
+    assert config.shortcuts.language_dictation == {}
+    assert config.shortcuts.double_tap_modifier is None
+    assert config.shortcuts.double_tap_interval_ms == 300
+    assert config.shortcuts.push_to_talk_min_hold_ms == 250
+    assert config.injection.backend == "auto"
+    assert config.injection.focus_lock == "pause"
+    assert "keepassxc" in config.injection.deny_list
+    assert "sudo" in config.injection.deny_list
+    assert config.injection.allow_list == []
+    assert config.injection.blocked_action == "clipboard"
 
 
 def test_config_validation_beam_size_range():
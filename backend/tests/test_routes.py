@@ -76,6 +76,22 @@ async def test_get_config(client):
     assert "backend" in data
 
 
+@pytest.mark.asyncio
+async def test_get_languages(client, monkeypatch):
+    import src.main
+    from src.config import SUPPORTED_LANGUAGES, load_config
+
+    monkeypatch.setattr(src.main, "config", load_config())
+
+    resp = await client.get("/api/languages")
+    assert resp.status_code == 200
+    data = resp.json()
+    assert [lang["code"] for lang in data["languages"]] == list(SUPPORTED_LANGUAGES)
+    assert all(lang["name"] for lang in data["languages"])
+    assert data["auto_detect"] == "auto"
+    assert data["default"] == src.main.config.language
+
+
 @pytest.mark.asyncio
 async def test_get_config_returns_503_when_not_loaded(client):
     import src.main
@@ -225,6 +241,20 @@ async def test_list_audio_devices(client):
     assert data["devices"][0]["name"] == "Test Mic"
 
 
+@pytest.mark.asyncio
+async def test_list_audio_devices_rescan(client):
+    """GET /api/audio/devices?rescan=true asks for a fresh device scan."""
+    from unittest.mock import patch
+
+    with patch(
+        "src.api.routes.audio.AudioCapture.list_devices", return_value=[]
+    ) as list_devices:
+        resp = await client.get("/api/audio/devices", params={"rescan": "true"})
+
+    assert resp.status_code == 200
+    list_devices.assert_called_once_with(rescan=True)
+
+
 # ── GET /api/sessions/search tests ────────────────────────────────
 
 
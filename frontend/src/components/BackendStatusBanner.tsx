@@ -2,12 +2,21 @@ import { useBackendHealth } from "@/hooks/useBackendHealth";
 import { cn } from "@/lib/utils";
 
 export function BackendStatusBanner() {
-  const { status, checks } = useBackendHealth();
+  const { status, checks, backendProcess } = useBackendHealth();
 
   if (status === "healthy" || status === "unknown") return null;
 
   const messages: string[] = [];
-  if (status === "unreachable") {
+  if (status === "unreachable" && backendProcess?.status === "starting") {
+    messages.push("Backend is starting…");
+  } else if (status === "unreachable" && backendProcess?.status === "restarting") {
+    messages.push(`Backend stopped unexpectedly: ${backendProcess.message ?? "restarting"}`);
+  } else if (status === "unreachable" && backendProcess?.status === "failed") {
+    messages.push(
+      `Backend failed to start${backendProcess.message ? `: ${backendProcess.message}` : ""}. ` +
+        "Restart it from the tray menu.",
+    );
+  } else if (status === "unreachable") {
     messages.push("Backend is unreachable");
   } else {
     if (checks.transcription?.status === "error") messages.push("Transcription engine unavailable");
@@ -15,7 +24,9 @@ export function BackendStatusBanner() {
     if (checks.database?.status === "error") messages.push("Database error");
   }
 
-  const isError = status === "unreachable" || status === "unhealthy";
+  const isStarting =
+    backendProcess?.status === "starting" || backendProcess?.status === "restarting";
+  const isError = (status === "unreachable" && !isStarting) || status === "unhealthy";
 
   return (
     <div
@@ -1,7 +1,8 @@
 import { useEffect } from "react";
-import { Link, Outlet, useLocation } from "react-router-dom";
+import { Link, Outlet, useLocation, useNavigate } from "react-router-dom";
 import { Mic, FileAudio, History, Settings } from "lucide-react";
 import { cn } from "@/lib/utils";
+import { listenEvent } from "@/lib/tauri";
 import { BackendStatusBanner } from "@/components/BackendStatusBanner";
 import { ThemeToggle } from "@/components/ThemeToggle";
 import { LogoMark } from "@/components/LogoMark";
@@ -16,12 +17,22 @@ const navItems = [
 
 export function Layout() {
   const location = useLocation();
+  const navigate = useNavigate();
 
   // Scroll to top on navigation
   useEffect(() => {
     window.scrollTo(0, 0);
   }, [location.pathname]);
 
+  // "Open" on a session in the tray's Recent menu
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    listenEvent<number>("tray:open-session", (id) => {
+      navigate(`/sessions/${id}`);
+    }).then((fn) => (unlisten = fn));
+    return () => unlisten?.();
+  }, [navigate]);
+
   return (
     <TooltipProvider delayDuration={300}>
       <div className="min-h-screen bg-background text-foreground">
@@ -3,7 +3,7 @@ import { Mic, MicOff } from "lucide-react";
 
 import { Button } from "@/components/ui/button";
 import { cn } from "@/lib/utils";
-import { WS_URL } from "@/lib/constants";
+import { openWebSocket, wsUrl } from "@/lib/api";
 
 const MAX_DURATION_S = 15;
 
@@ -33,7 +33,7 @@ export function MicTest({ device }: MicTestProps) {
     setError(null);
     setRms(0);
 
-    const ws = new WebSocket(`${WS_URL}/ws/mic-test`);
+    const ws = openWebSocket(wsUrl("/ws/mic-test"));
     wsRef.current = ws;
 
     ws.onopen = () => {
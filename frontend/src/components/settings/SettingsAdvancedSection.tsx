@@ -39,6 +39,13 @@ export function SettingsAdvancedSection({ draft }: Props) {
       <CardContent className="space-y-3">
         <InfoRow label="Backend" value={`${draft.backend.host}:${draft.backend.port}`} />
         <InfoRow label="Database" value={draft.storage.db_path} />
+        <InfoRow label="Text injection" value={draft.injection.backend} />
+        <InfoRow label="Focus lock" value={draft.injection.focus_lock} />
+        <InfoRow
+          label="Blocked apps"
+          value={`${draft.injection.deny_list.length} denied, ${draft.injection.allow_list.length} allowed`}
+        />
+        <InfoRow label="Blocked text" value={draft.injection.blocked_action} />
       </CardContent>
     </Card>
   );
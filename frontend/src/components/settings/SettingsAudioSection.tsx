@@ -54,7 +54,7 @@ export function SettingsAudioSection({ draft, devices, set }: Props) {
             <SelectContent>
               <SelectItem value="default">Default</SelectItem>
               {devices.map((d) => (
-                <SelectItem key={d.index} value={String(d.index)}>
+                <SelectItem key={d.name} value={d.name}>
                   {d.name}
                 </SelectItem>
               ))}
@@ -1,3 +1,4 @@
+import { useEffect, useState } from "react";
 import { Globe } from "lucide-react";
 
 import {
@@ -7,17 +8,157 @@ import {
   CardHeader,
   CardTitle,
 } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
 import { Label } from "@/components/ui/label";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
 import { Slider } from "@/components/ui/slider";
+import { Switch } from "@/components/ui/switch";
 import { LanguageSelector } from "@/components/LanguageSelector";
-import type { AppConfig } from "@/lib/api";
+import type { AppConfig, ShortcutsConfig } from "@/lib/api";
+import { invokeCommand, isCommandError, isTauri } from "@/lib/tauri";
+import type { ShortcutBindingResult } from "@/lib/tauri";
+import { LANGUAGES } from "@/lib/constants";
+
+const SHORTCUT_ROWS: {
+  key: Exclude<
+    keyof ShortcutsConfig,
+    | "dictation_mode"
+    | "push_to_talk_min_hold_ms"
+    | "language_dictation"
+    | "double_tap_modifier"
+    | "double_tap_interval_ms"
+  >;
+  label: string;
+}[] = [
+  { key: "toggle_dictation", label: "Toggle dictation" },
+  { key: "toggle_transcription", label: "Toggle transcription" },
+  { key: "undo_dictation", label: "Undo last dictation" },
+  { key: "cancel_session", label: "Cancel dictation" },
+  { key: "cycle_language", label: "Next language" },
+  { key: "toggle_overlay", label: "Show/hide overlay" },
+  { key: "open_main_window", label: "Open main window" },
+  { key: "paste_last_transcript", label: "Paste last transcript" },
+  { key: "pause_transcription", label: "Pause/resume transcription" },
+];
+
+/** Radix Select needs a non-empty value for "off". */
+const DOUBLE_TAP_OFF = "off";
+
+const DOUBLE_TAP_MODIFIERS: {
+  value: NonNullable<ShortcutsConfig["double_tap_modifier"]>;
+  label: string;
+}[] = [
+  { value: "right_ctrl", label: "Right Ctrl" },
+  { value: "left_ctrl", label: "Left Ctrl" },
+  { value: "right_alt", label: "Right Alt" },
+  { value: "left_alt", label: "Left Alt" },
+  { value: "right_shift", label: "Right Shift" },
+  { value: "left_shift", label: "Left Shift" },
+  { value: "right_super", label: "Right Super" },
+  { value: "left_super", label: "Left Super" },
+];
+
+/** Why a shortcut is not active, or null if it is (or status is unknown). */
+function shortcutProblem(result?: ShortcutBindingResult): string | null {
+  switch (result?.status) {
+    case "in_use":
+      return "In use by another app";
+    case "invalid":
+      return `Invalid shortcut: ${result.message}`;
+    case "duplicate":
+      return `Same as ${result.other.replace(/_/g, " ")}`;
+    case "unavailable":
+      return result.message;
+    default:
+      return null;
+  }
+}
+
+function ShortcutRow({
+  action,
+  label,
+  accelerator,
+  result,
+  onChange,
+}: {
+  action: string;
+  label: string;
+  accelerator: string;
+  result?: ShortcutBindingResult;
+  onChange: (accelerator: string) => void;
+}) {
+  const [recording, setRecording] = useState(false);
+  // Check of a freshly recorded shortcut, until the next registration
+  const [captured, setCaptured] = useState<ShortcutBindingResult | null>(null);
+  const [captureError, setCaptureError] = useState<string | null>(null);
+
+  useEffect(() => setCaptured(null), [result]);
+
+  const record = async () => {
+    setRecording(true);
+    setCaptureError(null);
+    try {
+      const next = await invokeCommand<ShortcutBindingResult | null>(
+        "capture_shortcut",
+        { action },
+      );
+      if (next) {
+        setCaptured(next);
+        onChange(next.accelerator);
+      }
+    } catch (e) {
+      setCaptureError(isCommandError(e) ? e.message : String(e));
+    } finally {
+      setRecording(false);
+    }
+  };
+
+  const problem = captureError ?? shortcutProblem(captured ?? result);
+  return (
+    <div className="flex items-center justify-between">
+      <div>
+        <Label className="text-muted-foreground">{label}</Label>
+        {problem && <p className="text-xs text-destructive">{problem}</p>}
+      </div>
+      <div className="flex items-center gap-2">
+        <span className="rounded bg-muted px-2 py-1 text-xs font-mono">
+          {recording ? "Press keys…" : accelerator || "Disabled"}
+        </span>
+        {isTauri() && (
+          <Button
+            variant="outline"
+            size="xs"
+            disabled={recording}
+            onClick={record}
+          >
+            Record
+          </Button>
+        )}
+      </div>
+    </div>
+  );
+}
 
 interface Props {
   draft: AppConfig;
   updateDraft: (updater: (prev: AppConfig) => AppConfig) => void;
+  shortcutStatus: ShortcutBindingResult[];
 }
 
-export function SettingsGeneralSection({ draft, updateDraft }: Props) {
+export function SettingsGeneralSection({
+  draft,
+  updateDraft,
+  shortcutStatus,
+}: Props) {
+  const doubleTapProblem = shortcutProblem(
+    shortcutStatus.find((r) => r.action === "double_tap_modifier"),
+  );
   return (
     <Card className="border-accent-top">
       <CardHeader>
@@ -62,19 +203,105 @@ export function SettingsGeneralSection({ draft, updateDraft }: Props) {
         </div>
 
         <div className="flex items-center justify-between">
-          <Label className="text-muted-foreground">Toggle dictation</Label>
-          <span className="rounded bg-muted px-2 py-1 text-xs font-mono">
-            {draft.shortcuts.toggle_dictation}
-          </span>
+          <div>
+            <Label>Push-to-talk</Label>
+            <p className="text-xs text-muted-foreground">
+              Dictate while the dictation shortcut is held down
+            </p>
+          </div>
+          <Switch
+            checked={draft.shortcuts.dictation_mode === "push_to_talk"}
+            onCheckedChange={(v) =>
+              updateDraft((p) => ({
+                ...p,
+                shortcuts: {
+                  ...p.shortcuts,
+                  dictation_mode: v ? "push_to_talk" : "toggle",
+                },
+              }))
+            }
+          />
         </div>
+
         <div className="flex items-center justify-between">
-          <Label className="text-muted-foreground">
-            Toggle transcription
-          </Label>
-          <span className="rounded bg-muted px-2 py-1 text-xs font-mono">
-            {draft.shortcuts.toggle_transcription}
-          </span>
+          <div>
+            <Label>Double-tap to dictate</Label>
+            <p className="text-xs text-muted-foreground">
+              Tap a modifier twice to toggle dictation (X11 and Windows)
+            </p>
+            {doubleTapProblem && (
+              <p className="text-xs text-destructive">{doubleTapProblem}</p>
+            )}
+          </div>
+          <Select
+            value={draft.shortcuts.double_tap_modifier ?? DOUBLE_TAP_OFF}
+            onValueChange={(v) =>
+              updateDraft((p) => ({
+                ...p,
+                shortcuts: {
+                  ...p.shortcuts,
+                  double_tap_modifier:
+                    v === DOUBLE_TAP_OFF
+                      ? null
+                      : (v as ShortcutsConfig["double_tap_modifier"]),
+                },
+              }))
+            }
+          >
+            <SelectTrigger className="w-[160px]">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value={DOUBLE_TAP_OFF}>Off</SelectItem>
+              {DOUBLE_TAP_MODIFIERS.map((m) => (
+                <SelectItem key={m.value} value={m.value}>
+                  {m.label}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
         </div>
+
+        {SHORTCUT_ROWS.map(({ key, label }) => (
+          <ShortcutRow
+            key={key}
+            action={key}
+            label={label}
+            accelerator={draft.shortcuts[key]}
+            result={shortcutStatus.find((r) => r.action === key)}
+            onChange={(v) =>
+              updateDraft((p) => ({
+                ...p,
+                shortcuts: { ...p.shortcuts, [key]: v },
+              }))
+            }
+          />
+        ))}
+        {Object.entries(draft.shortcuts.language_dictation).map(
+          ([code, accelerator]) => (
+            <ShortcutRow
+              key={code}
+              action={`language_dictation.${code}`}
+              label={`Dictate in ${LANGUAGES.find((l) => l.code === code)?.label ?? code}`}
+              accelerator={accelerator}
+              result={shortcutStatus.find(
+                (r) => r.action === `language_dictation.${code}`,
+              )}
+              onChange={(v) =>
+                updateDraft((p) => ({
+                  ...p,
+                  shortcuts: {
+                    ...p.shortcuts,
+                    language_dictation: {
+                      ...p.shortcuts.language_dictation,
+                      [code]: v,
+                    },
+                  },
+                }))
+              }
+            />
+          ),
+        )}
       </CardContent>
     </Card>
   );
@@ -5,8 +5,8 @@ import type { UseTranscriptionReturn } from "@/hooks/useTranscription";
 import { useDictation } from "@/hooks/useDictation";
 import type { UseDictationReturn } from "@/hooks/useDictation";
 import { useTauriShortcuts } from "@/hooks/useTauriShortcuts";
-import { emitEvent, listenEvent } from "@/lib/tauri";
-import { DEFAULT_LANGUAGE } from "@/lib/constants";
+import { emitEvent, invokeCommand, listenEvent } from "@/lib/tauri";
+import { DEFAULT_LANGUAGE, LANGUAGES } from "@/lib/constants";
 
 export interface TranscriptionContextValue {
   language: string;
@@ -38,14 +38,56 @@ export function TranscriptionProvider({
 
   // Global shortcuts — live at app level so they work on any page
   useTauriShortcuts({
-    onToggleDictation: () => dictation.toggle(language),
-    onToggleTranscription: () => {
+    onToggleDictation: (fixedLanguage) => {
+      // Language shortcuts pick the language a new session starts in
+      if (fixedLanguage && !isDictating) setLanguage(fixedLanguage);
+      dictation.toggle(fixedLanguage ?? language);
+    },
+    onStartDictation: (fixedLanguage) => {
+      if (isDictating) return;
+      if (fixedLanguage) setLanguage(fixedLanguage);
+      dictation.toggle(fixedLanguage ?? language);
+    },
+    onStopDictation: () => {
+      // A session already finalizing stops by itself
+      if (isDictating && dictation.state !== "finalizing") {
+        dictation.toggle(language);
+      }
+    },
+    onToggleTranscription: (fixedLanguage) => {
+      if (isTranscribing) {
+        transcription.stop();
+        return;
+      }
+      // `openwhisper start-transcription --lang` picks the language
+      if (fixedLanguage) setLanguage(fixedLanguage);
+      if (transcription.liveText) {
+        transcription.resume(fixedLanguage ?? language);
+      } else {
+        transcription.start(fixedLanguage ?? language);
+      }
+    },
+    onCancelSession: () => {
+      if (isDictating) dictation.cancel();
+    },
+    onCycleLanguage: () => {
+      // Same rule as the tray: the language is fixed while a session runs
+      if (isActive) return;
+      const index = LANGUAGES.findIndex((l) => l.code === language);
+      setLanguage(LANGUAGES[(index + 1) % LANGUAGES.length].code);
+    },
+    onPasteLastTranscript: () => {
+      const text = dictation.lastTranscript || transcription.liveText;
+      if (!text) return;
+      invokeCommand("enqueue_injection", { text }).catch((e) =>
+        console.error("[Shortcuts] paste last transcript failed:", e),
+      );
+    },
+    onPauseTranscription: () => {
       if (isTranscribing) {
         transcription.stop();
       } else if (transcription.liveText) {
         transcription.resume(language);
-      } else {
-        transcription.start(language);
       }
     },
   });
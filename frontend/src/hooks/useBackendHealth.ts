@@ -1,5 +1,7 @@
 import { useState, useEffect, useCallback } from "react";
 import { fetchHealth } from "@/lib/api";
+import { invokeCommand, isTauri, listenEvent } from "@/lib/tauri";
+import type { BackendProcessStatus } from "@/lib/tauri";
 
 export type BackendStatus =
   | "unknown"
@@ -18,6 +20,8 @@ export function useBackendHealth(intervalMs = 15000) {
   const [status, setStatus] = useState<BackendStatus>("unknown");
   const [checks, setChecks] = useState<HealthChecks>({});
   const [lastChecked, setLastChecked] = useState<Date | null>(null);
+  // Desktop app only: the backend process it starts and supervises
+  const [backendProcess, setBackendProcess] = useState<BackendProcessStatus | null>(null);
 
   const refresh = useCallback(() => {
     fetchHealth()
@@ -39,5 +43,19 @@ export function useBackendHealth(intervalMs = 15000) {
     return () => clearInterval(id);
   }, [refresh, intervalMs]);
 
-  return { status, checks, lastChecked, refresh };
+  useEffect(() => {
+    if (!isTauri()) return;
+    let unlisten: (() => void) | undefined;
+    invokeCommand<BackendProcessStatus>("get_backend_status").then((current) => {
+      if (current) setBackendProcess(current);
+    });
+    listenEvent<BackendProcessStatus>("backend:status", (payload) => {
+      setBackendProcess(payload);
+      // Don't wait for the next poll to drop the "unreachable" banner
+      if (payload.status === "ready") refresh();
+    }).then((fn) => (unlisten = fn));
+    return () => unlisten?.();
+  }, [refresh]);
+
+  return { status, checks, lastChecked, refresh, backendProcess };
 }
@@ -1,7 +1,13 @@
 import { useCallback, useRef, useState } from "react";
 import { useWebSocket } from "./useWebSocket";
-import { WS_URL, DEFAULT_LANGUAGE } from "@/lib/constants";
-import { invokeCommand } from "@/lib/tauri";
+import { DEFAULT_LANGUAGE } from "@/lib/constants";
+import { wsUrl } from "@/lib/api";
+import {
+  commandErrorRecovery,
+  emitEvent,
+  invokeCommand,
+  isCommandError,
+} from "@/lib/tauri";
 
 export type DictationState =
   | "idle"
@@ -14,7 +20,11 @@ export type DictationState =
 export interface UseDictationReturn {
   state: DictationState;
   toggle: (language?: string) => void;
+  /** Abort the session; nothing more is typed. */
+  cancel: () => void;
   error: string | null;
+  /** Final text of the last (or current) dictation session. */
+  lastTranscript: string;
   device: string | null;
 }
 
@@ -27,41 +37,33 @@ export function useDictation(): UseDictationReturn {
   const [state, setState] = useState<DictationState>("idle");
   const [error, setError] = useState<string | null>(null);
   const [device, setDevice] = useState<string | null>(null);
+  const [lastTranscript, setLastTranscript] = useState("");
   const languageRef = useRef(DEFAULT_LANGUAGE);
 
-  // Buffer deltas and inject in batches to avoid dropped keystrokes
-  const bufferRef = useRef("");
-  const flushTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
-  const injectingRef = useRef(false);
-
-  const flushBuffer = useCallback(() => {
-    flushTimerRef.current = null;
-    if (injectingRef.current || !bufferRef.current) return;
-
-    const text = bufferRef.current;
-    bufferRef.current = "";
-    injectingRef.current = true;
-
-    invokeCommand("inject_text", { text })
-      .then(() => console.log("[Dictation] injected:", text))
-      .catch((e) => console.error("[Dictation] inject_text failed:", e))
-      .finally(() => {
-        injectingRef.current = false;
-        // Flush remaining buffer after previous injection completes
-        if (bufferRef.current) {
-          flushBuffer();
-        }
-      });
-  }, []);
+  // Deltas go to the Rust injection queue, which preserves their order and
+  // injects on its own worker thread. Each enqueue waits for the previous
+  // one so the IPC calls themselves cannot overtake each other.
+  const enqueueChainRef = useRef<Promise<unknown>>(Promise.resolve());
+
+  const enqueue = useCallback(
+    (cmd: string, args: Record<string, unknown>) => {
+      enqueueChainRef.current = enqueueChainRef.current
+        .then(() => invokeCommand<number>(cmd, args))
+        .then((id) => console.log(`[Dictation] ${cmd} queued`, id, args))
+        .catch((e) => {
+          console.error(`[Dictation] ${cmd} failed:`, e);
+          // Setup problems will not fix themselves; tell the user
+          if (isCommandError(e) && commandErrorRecovery(e) === "help") {
+            setError(e.message);
+          }
+        });
+    },
+    [],
+  );
 
   const queueDelta = useCallback(
-    (delta: string) => {
-      bufferRef.current += delta;
-      if (!flushTimerRef.current && !injectingRef.current) {
-        flushTimerRef.current = setTimeout(flushBuffer, 80);
-      }
-    },
-    [flushBuffer],
+    (delta: string) => enqueue("enqueue_injection", { text: delta }),
+    [enqueue],
   );
 
   const handleMessage = useCallback(
@@ -70,6 +72,8 @@ export function useDictation(): UseDictationReturn {
       switch (msg.type) {
         case "session_started":
           console.log("[Dictation] session started");
+          setLastTranscript("");
+          enqueue("begin_injection_session", { sessionId: msg.session_id });
           break;
 
         case "status":
@@ -92,18 +96,20 @@ export function useDictation(): UseDictationReturn {
         }
 
         case "segment_complete":
+          // Correct the provisional deltas with the final text
+          enqueue("finalize_injected_segment", { text: msg.text });
+          setLastTranscript((prev) => prev + (msg.text as string));
           break;
 
         case "session_ended":
-          // Flush any remaining buffered text before ending
-          if (bufferRef.current) {
-            flushBuffer();
-          }
+          enqueue("end_injection_session", {});
+          emitEvent("session-ended", msg.session_id);
           setState("idle");
           disconnect();
           break;
 
         case "error":
+          enqueue("end_injection_session", {});
           setError(msg.message as string);
           setState("error");
           disconnect();
@@ -136,12 +142,23 @@ export function useDictation(): UseDictationReturn {
   }, []);
 
   const { send, connect, disconnect } = useWebSocket(
-    `${WS_URL}/ws/transcribe`,
+    wsUrl("/ws/transcribe"),
     handleMessage,
     handleOpen,
     handleClose,
   );
 
+  const cancel = useCallback(() => {
+    setState("idle");
+    disconnect();
+    invokeCommand("cancel_injection").catch((e) =>
+      console.error("[Dictation] cancel_injection failed:", e),
+    );
+    invokeCommand("end_injection_session").catch((e) =>
+      console.error("[Dictation] end_injection_session failed:", e),
+    );
+  }, [disconnect]);
+
   const toggle = useCallback(
     (language?: string) => {
       console.log("[Dictation] toggle called, state:", state);
@@ -156,12 +173,11 @@ export function useDictation(): UseDictationReturn {
         send({ type: "stop" });
       } else {
         // connecting, loading_model, finalizing — force stop
-        setState("idle");
-        disconnect();
+        cancel();
       }
     },
-    [state, connect, send],
+    [state, connect, send, cancel],
   );
 
-  return { state, toggle, error, device };
+  return { state, toggle, cancel, error, device, lastTranscript };
 }
@@ -1,6 +1,6 @@
 import { useCallback, useEffect, useRef, useState } from "react";
-import { WS_URL } from "@/lib/constants";
-import { uploadFileForTranscription } from "@/lib/api";
+import { openWebSocket, uploadFileForTranscription, wsUrl } from "@/lib/api";
+import { emitEvent } from "@/lib/tauri";
 
 export type FileTranscriptionState =
   | "idle"
@@ -78,7 +78,7 @@ export function useFileTranscription(): UseFileTranscriptionReturn {
 
       // Connect WebSocket for progress
       setState("connecting");
-      const ws = new WebSocket(`${WS_URL}/ws/transcribe-file/${sid}`);
+      const ws = openWebSocket(wsUrl(`/ws/transcribe-file/${sid}`));
       wsRef.current = ws;
 
       ws.onopen = () => {
@@ -112,6 +112,7 @@ export function useFileTranscription(): UseFileTranscriptionReturn {
               break;
 
             case "session_ended":
+              emitEvent("session-ended", msg.session_id);
               setState("completed");
               closeWs();
               break;
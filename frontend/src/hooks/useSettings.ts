@@ -6,7 +6,8 @@ import {
   fetchAudioDevices,
 } from "@/lib/api";
 import type { AppConfig, AudioDevice, UpdateConfigResult } from "@/lib/api";
-import { emitEvent, setWindowVisible } from "@/lib/tauri";
+import { emitEvent, invokeCommand, setWindowVisible } from "@/lib/tauri";
+import type { ShortcutBindingResult } from "@/lib/tauri";
 
 export interface UseSettingsReturn {
   /** Server-side config (last fetched). */
@@ -20,6 +21,8 @@ export interface UseSettingsReturn {
   error: string | null;
   /** Result from the last save operation. */
   saveResult: UpdateConfigResult | null;
+  /** Per-binding result of the last global shortcut registration. */
+  shortcutStatus: ShortcutBindingResult[];
   /** Replace a section of the draft. */
   updateDraft: (updater: (prev: AppConfig) => AppConfig) => void;
   /** Persist draft to backend. */
@@ -38,6 +41,16 @@ export function useSettings(): UseSettingsReturn {
   const [saving, setSaving] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [saveResult, setSaveResult] = useState<UpdateConfigResult | null>(null);
+  const [shortcutStatus, setShortcutStatus] = useState<ShortcutBindingResult[]>(
+    [],
+  );
+
+  // Registration results from startup, so conflicts show without a save
+  useEffect(() => {
+    invokeCommand<ShortcutBindingResult[]>("get_shortcut_status")
+      .then((status) => status && setShortcutStatus(status))
+      .catch((e) => console.error("[Settings] get_shortcut_status failed:", e));
+  }, []);
 
   // Fetch config + devices on mount
   useEffect(() => {
@@ -130,9 +143,45 @@ export function useSettings(): UseSettingsReturn {
       setDraft(structuredClone(freshConfig));
 
       // Emit Tauri events for hot-reload sync
+      await emitEvent("config-changed", null);
       if (config && freshConfig.language !== config.language) {
         await emitEvent("language-changed", freshConfig.language);
       }
+      if (config && freshConfig.injection.backend !== config.injection.backend) {
+        await invokeCommand("set_injection_backend", {
+          backend: freshConfig.injection.backend,
+        });
+      }
+      if (config && freshConfig.injection.focus_lock !== config.injection.focus_lock) {
+        await invokeCommand("set_focus_lock", {
+          mode: freshConfig.injection.focus_lock,
+        });
+      }
+      const policyKeys = ["deny_list", "allow_list", "blocked_action"] as const;
+      if (
+        config &&
+        policyKeys.some(
+          (key) =>
+            JSON.stringify(freshConfig.injection[key]) !==
+            JSON.stringify(config.injection[key]),
+        )
+      ) {
+        await invokeCommand("set_injection_policy", {
+          denyList: freshConfig.injection.deny_list,
+          allowList: freshConfig.injection.allow_list,
+          blockedAction: freshConfig.injection.blocked_action,
+        });
+      }
+      if (
+        config &&
+        JSON.stringify(freshConfig.shortcuts) !== JSON.stringify(config.shortcuts)
+      ) {
+        const status = await invokeCommand<ShortcutBindingResult[]>(
+          "apply_shortcuts",
+          { shortcuts: freshConfig.shortcuts },
+        );
+        if (status) setShortcutStatus(status);
+      }
       // Note: overlay events are handled live via the dedicated useEffect
     } catch (err) {
       setError((err as Error).message);
@@ -149,6 +198,7 @@ export function useSettings(): UseSettingsReturn {
     saving,
     error,
     saveResult,
+    shortcutStatus,
     updateDraft,
     save,
     reset,
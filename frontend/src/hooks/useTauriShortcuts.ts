@@ -1,11 +1,38 @@
 import { useEffect, useRef } from "react";
 import { listenEvent } from "@/lib/tauri";
 
+/** Language-specific dictation shortcuts pass their language. */
+type ShortcutHandler = (language?: string) => void;
+
 interface UseTauriShortcutsOptions {
-  onToggleDictation?: () => void;
-  onToggleTranscription?: () => void;
+  onToggleDictation?: ShortcutHandler;
+  /** Push-to-talk: the dictation shortcut is being held. */
+  onStartDictation?: ShortcutHandler;
+  /** Push-to-talk: the dictation shortcut was released. */
+  onStopDictation?: ShortcutHandler;
+  onToggleTranscription?: ShortcutHandler;
+  onCancelSession?: ShortcutHandler;
+  onCycleLanguage?: ShortcutHandler;
+  onPasteLastTranscript?: ShortcutHandler;
+  onPauseTranscription?: ShortcutHandler;
+}
+
+interface ShortcutPayload {
+  language?: string | null;
 }
 
+/** Events handled here; overlay and main window shortcuts are handled in Rust. */
+const EVENT_HANDLERS: [string, keyof UseTauriShortcutsOptions][] = [
+  ["shortcut:toggle-dictation", "onToggleDictation"],
+  ["shortcut:dictation-start", "onStartDictation"],
+  ["shortcut:dictation-stop", "onStopDictation"],
+  ["shortcut:toggle-transcription", "onToggleTranscription"],
+  ["shortcut:cancel-session", "onCancelSession"],
+  ["shortcut:cycle-language", "onCycleLanguage"],
+  ["shortcut:paste-last-transcript", "onPasteLastTranscript"],
+  ["shortcut:pause-transcription", "onPauseTranscription"],
+];
+
 /**
  * Listens for global shortcut events emitted by Tauri (Rust).
  * No-op when running in a plain browser.
@@ -18,27 +45,18 @@ export function useTauriShortcuts(options: UseTauriShortcutsOptions) {
     let aborted = false;
     const cleanups: Array<() => void> = [];
 
-    listenEvent("shortcut:toggle-dictation", () => {
-      console.log("[Shortcuts] toggle-dictation event received");
-      optionsRef.current.onToggleDictation?.();
-    }).then((unlisten) => {
-      if (aborted) {
-        unlisten();
-      } else {
-        cleanups.push(unlisten);
-      }
-    });
-
-    listenEvent("shortcut:toggle-transcription", () => {
-      console.log("[Shortcuts] toggle-transcription event received");
-      optionsRef.current.onToggleTranscription?.();
-    }).then((unlisten) => {
-      if (aborted) {
-        unlisten();
-      } else {
-        cleanups.push(unlisten);
-      }
-    });
+    for (const [event, handler] of EVENT_HANDLERS) {
+      listenEvent<ShortcutPayload | null>(event, (payload) => {
+        console.log(`[Shortcuts] ${event} event received`, payload);
+        optionsRef.current[handler]?.(payload?.language ?? undefined);
+      }).then((unlisten) => {
+        if (aborted) {
+          unlisten();
+        } else {
+          cleanups.push(unlisten);
+        }
+      });
+    }
 
     return () => {
       aborted = true;
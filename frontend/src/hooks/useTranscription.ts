@@ -1,6 +1,8 @@
 import { useCallback, useRef, useState } from "react";
 import { useWebSocket } from "./useWebSocket";
-import { WS_URL, DEFAULT_LANGUAGE } from "@/lib/constants";
+import { DEFAULT_LANGUAGE } from "@/lib/constants";
+import { wsUrl } from "@/lib/api";
+import { emitEvent } from "@/lib/tauri";
 
 export type TranscriptionState =
   | "idle"
@@ -69,6 +71,7 @@ export function useTranscription(): UseTranscriptionReturn {
           break;
 
         case "session_ended":
+          emitEvent("session-ended", msg.session_id);
           setState("idle");
           disconnect();
           break;
@@ -105,7 +108,7 @@ export function useTranscription(): UseTranscriptionReturn {
   }, []);
 
   const { send, connect, disconnect } = useWebSocket(
-    `${WS_URL}/ws/transcribe`,
+    wsUrl("/ws/transcribe"),
     handleMessage,
     handleOpen,
     handleClose,
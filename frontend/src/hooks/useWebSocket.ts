@@ -1,4 +1,5 @@
 import { useCallback, useEffect, useRef, useState } from "react";
+import { openWebSocket } from "@/lib/api";
 
 export type WsState = "disconnected" | "connecting" | "connected";
 
@@ -41,7 +42,7 @@ export function useWebSocket(
     disconnect();
     setState("connecting");
 
-    const ws = new WebSocket(url);
+    const ws = openWebSocket(url);
     wsRef.current = ws;
 
     ws.onopen = () => {
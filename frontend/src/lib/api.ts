@@ -1,4 +1,56 @@
-import { BACKEND_URL } from "./constants";
+import { invokeCommand } from "./tauri";
+
+// ── Connection ─────────────────────────────────────────────────────
+
+/** Where the backend listens, and the token it expects. */
+export interface BackendConnection {
+  http_url: string;
+  ws_url: string;
+  token: string | null;
+}
+
+/** A backend started on its own (`npm run backend`) for the web frontend. */
+const STANDALONE: BackendConnection = {
+  http_url: "http://localhost:8001",
+  ws_url: "ws://localhost:8001",
+  token: null,
+};
+
+let connection = STANDALONE;
+
+/**
+ * Ask the desktop app which port and token it started the backend with.
+ * Called once before rendering; outside Tauri the standalone backend is used.
+ */
+export async function initBackendConnection(): Promise<void> {
+  try {
+    const fromApp = await invokeCommand<BackendConnection>("get_backend_connection");
+    if (fromApp) connection = fromApp;
+  } catch (e) {
+    console.error("[Backend] failed to get the backend connection", e);
+  }
+}
+
+/** WebSocket URL for `path`; open it with `openWebSocket`. */
+export function wsUrl(path: string): string {
+  return `${connection.ws_url}${path}`;
+}
+
+/**
+ * Open a WebSocket to the backend. WebSockets cannot send headers, so the
+ * token is offered as a subprotocol, keeping it out of the URL (and the
+ * backend's access log).
+ */
+export function openWebSocket(url: string): WebSocket {
+  if (!connection.token) return new WebSocket(url);
+  return new WebSocket(url, ["openwhisper", `openwhisper.token.${connection.token}`]);
+}
+
+function backendFetch(path: string, init: RequestInit = {}): Promise<Response> {
+  const headers = new Headers(init.headers);
+  if (connection.token) headers.set("Authorization", `Bearer ${connection.token}`);
+  return fetch(`${connection.http_url}${path}`, { ...init, headers });
+}
 
 export interface SessionSummary {
   id: number;
@@ -41,7 +93,7 @@ export interface HealthResponse {
 }
 
 export async function fetchHealth(): Promise<HealthResponse> {
-  const res = await fetch(`${BACKEND_URL}/health`);
+  const res = await backendFetch(`/health`);
   if (!res.ok) throw new Error("Backend unreachable");
   return res.json();
 }
@@ -50,8 +102,8 @@ export async function fetchSessions(
   limit = 50,
   offset = 0,
 ): Promise<SessionSummary[]> {
-  const res = await fetch(
-    `${BACKEND_URL}/api/sessions?limit=${limit}&offset=${offset}`,
+  const res = await backendFetch(
+    `/api/sessions?limit=${limit}&offset=${offset}`,
   );
   if (!res.ok) throw new Error("Failed to fetch sessions");
   const data = await res.json();
@@ -59,13 +111,13 @@ export async function fetchSessions(
 }
 
 export async function fetchSession(id: number): Promise<SessionDetail> {
-  const res = await fetch(`${BACKEND_URL}/api/sessions/${id}`);
+  const res = await backendFetch(`/api/sessions/${id}`);
   if (!res.ok) throw new Error("Failed to fetch session");
   return res.json();
 }
 
 export async function deleteSession(id: number): Promise<void> {
-  const res = await fetch(`${BACKEND_URL}/api/sessions/${id}`, {
+  const res = await backendFetch(`/api/sessions/${id}`, {
     method: "DELETE",
   });
   if (!res.ok) throw new Error("Failed to delete session");
@@ -92,8 +144,8 @@ export async function searchSessions(
       params.set(key, String(value));
     }
   }
-  const res = await fetch(
-    `${BACKEND_URL}/api/sessions/search?${params}`,
+  const res = await backendFetch(
+    `/api/sessions/search?${params}`,
   );
   if (!res.ok) throw new Error("Failed to search sessions");
   const data = await res.json();
@@ -105,6 +157,37 @@ export async function searchSessions(
 export interface ShortcutsConfig {
   toggle_dictation: string;
   toggle_transcription: string;
+  undo_dictation: string;
+  cancel_session: string;
+  cycle_language: string;
+  toggle_overlay: string;
+  open_main_window: string;
+  paste_last_transcript: string;
+  pause_transcription: string;
+  /** Language code -> shortcut that starts dictation in that language. */
+  language_dictation: Record<string, string>;
+  dictation_mode: "toggle" | "push_to_talk";
+  push_to_talk_min_hold_ms: number;
+  /** Modifier to double-tap to toggle dictation; null = off. */
+  double_tap_modifier:
+    | "left_ctrl"
+    | "right_ctrl"
+    | "left_alt"
+    | "right_alt"
+    | "left_shift"
+    | "right_shift"
+    | "left_super"
+    | "right_super"
+    | null;
+  double_tap_interval_ms: number;
+}
+
+export interface InjectionConfig {
+  backend: "auto" | "clipboard" | "unicode" | "xtest" | "wayland";
+  focus_lock: "off" | "refocus" | "pause";
+  deny_list: string[];
+  allow_list: string[];
+  blocked_action: "clipboard" | "drop";
 }
 
 export interface TranscriptionModelConfig {
@@ -166,6 +249,8 @@ export interface StorageConfig {
 export interface BackendConfig {
   host: string;
   port: number;
+  managed: boolean;
+  command: string[];
 }
 
 export interface SearchConfig {
@@ -177,6 +262,7 @@ export interface AppConfig {
   language: string;
   max_upload_size_mb: number;
   shortcuts: ShortcutsConfig;
+  injection: InjectionConfig;
   models: ModelsConfig;
   audio: AudioConfig;
   overlay: OverlayConfig;
@@ -201,7 +287,7 @@ export interface UpdateConfigResult {
 // ── Config API ─────────────────────────────────────────────────────
 
 export async function fetchFullConfig(): Promise<AppConfig> {
-  const res = await fetch(`${BACKEND_URL}/api/config`);
+  const res = await backendFetch(`/api/config`);
   if (!res.ok) throw new Error("Failed to fetch config");
   return res.json();
 }
@@ -209,7 +295,7 @@ export async function fetchFullConfig(): Promise<AppConfig> {
 export async function updateConfig(
   config: Partial<AppConfig>,
 ): Promise<UpdateConfigResult> {
-  const res = await fetch(`${BACKEND_URL}/api/config`, {
+  const res = await backendFetch(`/api/config`, {
     method: "PUT",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify(config),
@@ -222,7 +308,7 @@ export async function updateConfig(
 }
 
 export async function fetchAudioDevices(): Promise<AudioDevice[]> {
-  const res = await fetch(`${BACKEND_URL}/api/audio/devices`);
+  const res = await backendFetch(`/api/audio/devices`);
   if (!res.ok) throw new Error("Failed to fetch audio devices");
   const data = await res.json();
   return data.devices;
@@ -233,7 +319,7 @@ export async function fetchAudioDevices(): Promise<AudioDevice[]> {
 export async function summarizeSession(
   id: number,
 ): Promise<{ session_id: number; summary: string }> {
-  const res = await fetch(`${BACKEND_URL}/api/sessions/${id}/summarize`, {
+  const res = await backendFetch(`/api/sessions/${id}/summarize`, {
     method: "POST",
   });
   if (!res.ok) {
@@ -247,7 +333,7 @@ export async function rewriteText(
   text: string,
   instruction?: string,
 ): Promise<{ original: string; rewritten: string }> {
-  const res = await fetch(`${BACKEND_URL}/api/llm/rewrite`, {
+  const res = await backendFetch(`/api/llm/rewrite`, {
     method: "POST",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify({ text, instruction }),
@@ -273,7 +359,7 @@ export async function processText(
   scenario: Scenario,
   language: string,
 ): Promise<ProcessTextResult> {
-  const res = await fetch(`${BACKEND_URL}/api/llm/process`, {
+  const res = await backendFetch(`/api/llm/process`, {
     method: "POST",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify({ text, scenario, language }),
@@ -295,7 +381,7 @@ export async function uploadFileForTranscription(
   formData.append("file", file);
   formData.append("language", language);
 
-  const res = await fetch(`${BACKEND_URL}/api/transcribe/file`, {
+  const res = await backendFetch(`/api/transcribe/file`, {
     method: "POST",
     body: formData,
   });
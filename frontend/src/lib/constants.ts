@@ -1,4 +1,5 @@
 export const LANGUAGES = [
+  { code: "auto", label: "Auto-detect" },
   { code: "fr", label: "Fran\u00e7ais" },
   { code: "en", label: "English" },
   { code: "es", label: "Espa\u00f1ol" },
@@ -6,7 +7,7 @@ export const LANGUAGES = [
   { code: "it", label: "Italiano" },
   { code: "pt", label: "Portugu\u00eas" },
   { code: "nl", label: "Nederlands" },
-  { code: "pl", label: "Polski" },
+  { code: "hi", label: "\u0939\u093f\u0928\u094d\u0926\u0940" },
   { code: "ru", label: "\u0420\u0443\u0441\u0441\u043a\u0438\u0439" },
   { code: "zh", label: "\u4e2d\u6587" },
   { code: "ja", label: "\u65e5\u672c\u8a9e" },
@@ -16,5 +17,3 @@ export const LANGUAGES = [
 
 export const DEFAULT_LANGUAGE = "fr";
 
-export const BACKEND_URL = "http://localhost:8001";
-export const WS_URL = "ws://localhost:8001";
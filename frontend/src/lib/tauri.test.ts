@@ -0,0 +1,27 @@
+import { describe, it, expect } from "vitest";
+import { commandErrorRecovery, isCommandError } from "./tauri";
+
+describe("isCommandError", () => {
+  it("accepts errors serialized by Rust commands", () => {
+    expect(
+      isCommandError({ code: "NoDisplay", message: "Failed to init enigo" }),
+    ).toBe(true);
+  });
+
+  it("rejects plain strings and unrelated objects", () => {
+    expect(isCommandError("Injection worker stopped")).toBe(false);
+    expect(isCommandError(null)).toBe(false);
+    expect(isCommandError({ message: "no code" })).toBe(false);
+  });
+});
+
+describe("commandErrorRecovery", () => {
+  it("maps codes to a recovery action", () => {
+    const recovery = (code: Parameters<typeof commandErrorRecovery>[0]["code"]) =>
+      commandErrorRecovery({ code, message: "" });
+    expect(recovery("InputSimulationFailed")).toBe("retry");
+    expect(recovery("BackendUnavailable")).toBe("fallback");
+    expect(recovery("NoDisplay")).toBe("help");
+    expect(recovery("InjectionBlocked")).toBe("none");
+  });
+});
@@ -54,6 +54,88 @@ export async function emitEvent<T>(
   getBroadcastChannel().postMessage({ event, payload });
 }
 
+/** Stable error codes returned by Rust commands (`AppError` in src-tauri). */
+export type CommandErrorCode =
+  | "ClipboardUnavailable"
+  | "InputSimulationFailed"
+  | "MutexPoisoned"
+  | "NoDisplay"
+  | "BackendUnavailable"
+  | "TargetWindowChanged"
+  | "InjectionBlocked"
+  | "InjectionCancelled"
+  | "QueueStopped"
+  | "WindowOperationFailed"
+  | "ShortcutCaptureFailed"
+  | "ApiRequestFailed"
+  | "BackendProcessFailed";
+
+/** Rejection value of a failed Rust command. */
+export interface CommandError {
+  code: CommandErrorCode;
+  message: string;
+  /** BackendUnavailable: the backend that cannot run. */
+  backend?: string;
+  /** InjectionBlocked: why the focused app was refused. */
+  reason?: "denied" | "not_allowed" | "unknown";
+  /** InjectionBlocked: the app that was refused, if known. */
+  app?: string | null;
+}
+
+export function isCommandError(value: unknown): value is CommandError {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    typeof (value as CommandError).code === "string" &&
+    typeof (value as CommandError).message === "string"
+  );
+}
+
+/**
+ * What the UI should offer for a failed command: try again, switch to
+ * another injection backend, explain the setup problem, or nothing (the
+ * outcome was expected or is already reported through an event).
+ */
+export function commandErrorRecovery(
+  error: CommandError,
+): "retry" | "fallback" | "help" | "none" {
+  switch (error.code) {
+    case "ClipboardUnavailable":
+    case "InputSimulationFailed":
+      return "retry";
+    case "BackendUnavailable":
+      return "fallback";
+    case "NoDisplay":
+    case "MutexPoisoned":
+    case "QueueStopped":
+      return "help";
+    default:
+      return "none";
+  }
+}
+
+/**
+ * Registration outcome of one global shortcut (`apply_shortcuts`), or
+ * whether a recorded one can be bound (`capture_shortcut`, which adds
+ * "available").
+ */
+export type ShortcutBindingResult = {
+  /** Config key under `shortcuts`. */
+  action: string;
+  accelerator: string;
+} & (
+  | { status: "registered" | "disabled" | "available" }
+  | { status: "invalid" | "in_use" | "unavailable"; message: string }
+  | { status: "duplicate"; other: string }
+);
+
+/** Backend process supervised by the desktop app (`backend:status`). */
+export interface BackendProcessStatus {
+  status: "starting" | "ready" | "restarting" | "failed";
+  /** Why it is restarting or failed. */
+  message: string | null;
+}
+
 /**
  * Invoke a Tauri command (Rust #[tauri::command]).
  * No-op if not running in Tauri.
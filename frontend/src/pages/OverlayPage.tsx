@@ -25,6 +25,62 @@ interface OverlayConfigPayload {
   show_duration: boolean;
 }
 
+interface InjectionBlockedPayload {
+  reason: "denied" | "not_allowed" | "unknown";
+  app: string | null;
+  action: "clipboard" | "drop";
+  copied: boolean;
+}
+
+interface UndonePayload {
+  session_id: number;
+  erased: number;
+}
+
+interface UndoRefusedPayload {
+  session_id: number | null;
+  reason: "nothing_to_undo" | "window_changed" | "window_unknown" | "failed";
+}
+
+/** A short label on the capsule, with the full story in its tooltip. */
+interface Notice {
+  label: string;
+  className: string;
+  detail: string;
+}
+
+/** How long a notice stays on the capsule. */
+const NOTICE_MS = 4000;
+
+function describeBlocked(payload: InjectionBlockedPayload): string {
+  const app = payload.app ?? "this app";
+  const why =
+    payload.reason === "denied"
+      ? `${app} is on the deny-list`
+      : payload.reason === "not_allowed"
+        ? `${app} is not on the allow-list`
+        : "the focused app could not be identified";
+  const outcome = payload.copied
+    ? "text copied to clipboard"
+    : payload.action === "clipboard"
+      ? "text copied once the segment ends"
+      : "text discarded";
+  return `Not typed: ${why} (${outcome})`;
+}
+
+function describeUndoRefused(payload: UndoRefusedPayload): string {
+  switch (payload.reason) {
+    case "nothing_to_undo":
+      return "Nothing to undo";
+    case "window_changed":
+      return "Not undone: the dictation went to another window";
+    case "window_unknown":
+      return "Not undone: the focused window could not be identified";
+    default:
+      return "Not undone: erasing the text failed";
+  }
+}
+
 const modeColors = {
   transcription: {
     dot: "bg-amber-500",
@@ -123,6 +179,10 @@ export function OverlayPage() {
   const [showMode, setShowMode] = useState(false);
   const [showDuration, setShowDuration] = useState(false);
 
+  // Blocked injection or undo outcome, shown briefly
+  const [notice, setNotice] = useState<Notice | null>(null);
+  const noticeTimerRef = useRef<ReturnType<typeof setTimeout>>(undefined);
+
   // Recording duration timer
   const [elapsed, setElapsed] = useState(0);
   const intervalRef = useRef<ReturnType<typeof setInterval>>(undefined);
@@ -136,6 +196,12 @@ export function OverlayPage() {
     setShowDuration(cfg.show_duration);
   }
 
+  function showNotice(next: Notice) {
+    setNotice(next);
+    clearTimeout(noticeTimerRef.current);
+    noticeTimerRef.current = setTimeout(() => setNotice(null), NOTICE_MS);
+  }
+
   // Make body transparent for the overlay window
   useEffect(() => {
     document.documentElement.style.background = "transparent";
@@ -171,9 +237,12 @@ export function OverlayPage() {
   }, [micState.state]);
 
   // Compute window size: capsule + padding for ambient glow
-  const extraElements = [showLanguage, showMode, showDuration].filter(
-    Boolean,
-  ).length;
+  const extraElements = [
+    showLanguage,
+    showMode,
+    showDuration,
+    notice !== null,
+  ].filter(Boolean).length;
   const padding = 14;
   const capsuleW = 44 + extraElements * 38;
   const capsuleH = 30;
@@ -254,6 +323,37 @@ export function OverlayPage() {
     return () => cleanup?.();
   }, []);
 
+  // Listen for injections refused by the deny/allow-list, and for the
+  // outcome of "undo last dictation"
+  useEffect(() => {
+    const cleanups: (() => void)[] = [];
+    listenEvent<InjectionBlockedPayload>("injection:blocked", (payload) => {
+      showNotice({
+        label: "BLK",
+        className: "text-red-300",
+        detail: describeBlocked(payload),
+      });
+    }).then((unlisten) => cleanups.push(unlisten));
+    listenEvent<UndonePayload>("dictation:undone", (payload) => {
+      showNotice({
+        label: "UNDO",
+        className: "text-sky-300",
+        detail: `Undone: ${payload.erased} character(s) erased`,
+      });
+    }).then((unlisten) => cleanups.push(unlisten));
+    listenEvent<UndoRefusedPayload>("dictation:undo-refused", (payload) => {
+      showNotice({
+        label: "UNDO",
+        className: "text-stone-400 line-through",
+        detail: describeUndoRefused(payload),
+      });
+    }).then((unlisten) => cleanups.push(unlisten));
+    return () => {
+      cleanups.forEach((unlisten) => unlisten());
+      clearTimeout(noticeTimerRef.current);
+    };
+  }, []);
+
   const handleMouseDown = () => {
     invokeCommand("start_drag");
   };
@@ -294,6 +394,7 @@ export function OverlayPage() {
           "select-none cursor-grab active:cursor-grabbing",
         )}
         style={{ opacity: overlayOpacity }}
+        title={notice?.detail}
       >
         <div className="pointer-events-none flex items-center gap-2.5">
           {/* Indicator: waveform bars when recording, dot otherwise */}
@@ -346,6 +447,13 @@ export function OverlayPage() {
             </span>
           )}
 
+          {/* Blocked injection or undo outcome */}
+          {notice && (
+            <span className={cn("text-xs font-medium", notice.className)}>
+              {notice.label}
+            </span>
+          )}
+
           {/* Duration timer */}
           {showDuration && isRecording && (
             <span className="text-xs font-mono text-stone-300">
@@ -20,6 +20,7 @@ export function SettingsPage() {
     saving,
     error,
     saveResult,
+    shortcutStatus,
     updateDraft,
     save,
     reset,
@@ -80,7 +81,11 @@ export function SettingsPage() {
       )}
 
       {/* ── Sections ───────────────────────────────────────── */}
-      <SettingsGeneralSection draft={draft} updateDraft={updateDraft} />
+      <SettingsGeneralSection
+        draft={draft}
+        updateDraft={updateDraft}
+        shortcutStatus={shortcutStatus}
+      />
       <SettingsAudioSection draft={draft} devices={devices} set={set} />
       <SettingsOverlaySection draft={draft} set={set} />
       <SettingsTranscriptionSection draft={draft} set={set} />
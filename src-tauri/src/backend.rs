@@ -0,0 +1,190 @@
+//! Blocking client for the Python backend's HTTP API, for the parts of the
+//! shell (tray menus) that need backend data without going through a
+//! window, and supervision of the backend process itself.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+use crate::config::BackendConfig;
+use crate::error::AppError;
+
+mod process;
+
+pub use process::{restart, start, status, stop, BackendStatus, Status};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Between attempts while the backend is not up yet.
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Where the backend listens and the token it expects; what the webview
+/// gets from `get_backend_connection`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Connection {
+    pub http_url: String,
+    pub ws_url: String,
+    #[serde(skip)]
+    pub port: u16,
+    /// `None` for a backend the app does not start, which has none.
+    pub token: Option<String>,
+}
+
+static CONNECTION: OnceLock<Connection> = OnceLock::new();
+
+/// A port nothing listens on right now.
+fn free_port(host: &str) -> std::io::Result<u16> {
+    Ok(TcpListener::bind((host, 0))?.local_addr()?.port())
+}
+
+/// 32 random bytes, hex-encoded.
+fn random_token() -> Result<String, getrandom::Error> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Decide where the backend will listen: a free port and a fresh token
+/// when the app starts it, `backend.port` and no token otherwise.
+pub fn init(config: &BackendConfig) {
+    let (port, token) = if config.managed {
+        let port = free_port(&config.host).unwrap_or_else(|e| {
+            eprintln!("[Backend] no free port found, using {}: {e}", config.port);
+            config.port
+        });
+        match random_token() {
+            Ok(token) => (port, Some(token)),
+            Err(e) => {
+                eprintln!("[Backend] failed to generate a token, the API is unprotected: {e}");
+                (port, None)
+            }
+        }
+    } else {
+        (config.port, None)
+    };
+    let _ = CONNECTION.set(Connection {
+        http_url: format!("http://{}:{port}", config.host),
+        ws_url: format!("ws://{}:{port}", config.host),
+        port,
+        token,
+    });
+}
+
+fn connection() -> Result<&'static Connection, AppError> {
+    CONNECTION.get().ok_or_else(|| AppError::ApiRequestFailed {
+        message: "Backend connection not initialized".into(),
+    })
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into()
+}
+
+/// `Authorization` header value, when the backend expects a token.
+fn authorization(connection: &Connection) -> Option<String> {
+    connection
+        .token
+        .as_ref()
+        .map(|token| format!("Bearer {token}"))
+}
+
+fn request_failed(url: &str, e: impl std::fmt::Display) -> AppError {
+    AppError::ApiRequestFailed {
+        message: format!("{url}: {e}"),
+    }
+}
+
+/// GET `path` (e.g. `/api/languages`) and decode the JSON response.
+pub fn get<T: DeserializeOwned>(path: &str) -> Result<T, AppError> {
+    let connection = connection()?;
+    let url = format!("{}{path}", connection.http_url);
+    let mut request = agent().get(&url);
+    if let Some(value) = authorization(connection) {
+        request = request.header("Authorization", value);
+    }
+    request
+        .call()
+        .map_err(|e| request_failed(&url, e))?
+        .body_mut()
+        .read_json()
+        .map_err(|e| request_failed(&url, e))
+}
+
+/// Response of `PUT /api/config`.
+#[derive(Debug, Deserialize)]
+struct ConfigUpdate {
+    restart_required: Vec<String>,
+}
+
+/// Merge `patch` (a partial config, e.g. `{"audio": {"device": "3"}}`) into
+/// the backend's config, which validates and saves it.
+pub fn update_config(patch: &serde_json::Value) -> Result<(), AppError> {
+    let connection = connection()?;
+    let url = format!("{}/api/config", connection.http_url);
+    let mut request = agent().put(&url);
+    if let Some(value) = authorization(connection) {
+        request = request.header("Authorization", value);
+    }
+    let update: ConfigUpdate = request
+        .send_json(patch)
+        .map_err(|e| request_failed(&url, e))?
+        .body_mut()
+        .read_json()
+        .map_err(|e| request_failed(&url, e))?;
+    if !update.restart_required.is_empty() {
+        println!(
+            "[Backend] restart required for: {}",
+            update.restart_required.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// GET `path` on a thread of its own, retrying until the backend answers,
+/// then hand the response to `done`. `pending` is set meanwhile, so a fetch
+/// requested while one is still retrying is dropped rather than doubled.
+pub fn get_until_ready<T, F>(path: String, pending: &'static AtomicBool, done: F)
+where
+    T: DeserializeOwned,
+    F: FnOnce(T) + Send + 'static,
+{
+    if pending.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(move || {
+        let mut logged = false;
+        let response = loop {
+            match get(&path) {
+                Ok(response) => break response,
+                Err(e) if !logged => {
+                    println!("[Backend] {path} not available yet, retrying: {e}");
+                    logged = true;
+                }
+                Err(_) => {}
+            }
+            thread::sleep(RETRY_INTERVAL);
+        };
+        pending.store(false, Ordering::SeqCst);
+        done(response);
+    });
+}
+
+/// Whether the backend process is up, for windows opened after the last
+/// `backend:status` event.
+#[tauri::command]
+pub fn get_backend_status() -> BackendStatus {
+    status()
+}
+
+/// Where the webview should reach the backend, with the token to send.
+#[tauri::command]
+pub fn get_backend_connection() -> Result<Connection, AppError> {
+    connection().cloned()
+}
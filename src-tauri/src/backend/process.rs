@@ -0,0 +1,474 @@
+//! The Python backend as a child process of the app: started with it,
+//! restarted with exponential backoff when it crashes, and stopped when the
+//! app exits. Progress is broadcast as `backend:status`.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::BackendConfig;
+use crate::error::AppError;
+
+/// Read by the backend (`src/api/auth.py`).
+const PORT_ENV: &str = "OPENWHISPER_PORT";
+const TOKEN_ENV: &str = "OPENWHISPER_TOKEN";
+
+/// Where `uv` keeps the project's virtualenv.
+const VENV_ENV: &str = "UV_PROJECT_ENVIRONMENT";
+
+const HEALTH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the backend may go without answering `/health` or writing any
+/// output before it is given up on. Counted from its last line of output,
+/// so a first `uv run` still installing dependencies is not killed.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(180);
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Crashes in a row before giving up until the user restarts it.
+const MAX_RESTARTS: u32 = 5;
+
+/// A backend that stayed up this long had recovered: its crash count is
+/// reset.
+const STABLE_AFTER: Duration = Duration::from_secs(120);
+
+/// How long the backend gets to shut down before it is killed.
+#[cfg(unix)]
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Starting,
+    Ready,
+    Restarting,
+    /// Crashed too often, or could not be started at all.
+    Failed,
+}
+
+/// Payload of `backend:status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendStatus {
+    pub status: Status,
+    /// Why it is restarting or failed.
+    pub message: Option<String>,
+}
+
+static STATUS: Mutex<BackendStatus> = Mutex::new(BackendStatus {
+    status: Status::Starting,
+    message: None,
+});
+
+static CONFIG: OnceLock<BackendConfig> = OnceLock::new();
+
+/// The running backend, when the app started it.
+static CHILD: Mutex<Option<Backend>> = Mutex::new(None);
+
+/// When the backend last wrote a line to stdout or stderr.
+static LAST_OUTPUT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// The backend process and everything it starts: `uv run` runs the server
+/// as a child of its own, which has to go down with it. On unix the backend
+/// leads a process group of its own; on Windows it is put in a job object.
+struct Backend {
+    child: Child,
+    #[cfg(windows)]
+    job: Option<job::Job>,
+}
+
+impl Backend {
+    fn new(child: Child) -> Self {
+        #[cfg(windows)]
+        let job = match job::Job::assign(&child) {
+            Ok(job) => Some(job),
+            Err(e) => {
+                eprintln!("[Backend] no job object, its children may outlive it: {e}");
+                None
+            }
+        };
+        Self {
+            child,
+            #[cfg(windows)]
+            job,
+        }
+    }
+
+    /// Ask the whole group to shut down cleanly.
+    #[cfg(unix)]
+    fn terminate(&self) {
+        unsafe { libc::killpg(self.child.id() as libc::pid_t, libc::SIGTERM) };
+    }
+
+    /// Whether any process of the group is left, once the backend itself
+    /// has been reaped.
+    #[cfg(unix)]
+    fn group_alive(&mut self) -> bool {
+        let _ = self.child.try_wait();
+        unsafe { libc::killpg(self.child.id() as libc::pid_t, 0) == 0 }
+    }
+
+    /// Kill the backend and its children, and reap it.
+    fn kill(mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::killpg(self.child.id() as libc::pid_t, libc::SIGKILL);
+        }
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            job.terminate();
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(windows)]
+mod job {
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// A job object that kills its processes when closed, so they also
+    /// go away if the app is killed.
+    pub struct Job(HANDLE);
+
+    // The handle is only used through the `CHILD` mutex
+    unsafe impl Send for Job {}
+
+    impl Job {
+        pub fn assign(child: &Child) -> std::io::Result<Self> {
+            let handle = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+            if handle.is_null() {
+                return Err(std::io::Error::last_os_error());
+            }
+            let job = Job(handle);
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let ok = unsafe {
+                SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    &info as *const _ as *const _,
+                    std::mem::size_of_val(&info) as u32,
+                )
+            };
+            if ok == 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if unsafe { AssignProcessToJobObject(job.0, child.as_raw_handle() as HANDLE) } == 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(job)
+        }
+
+        pub fn terminate(&self) {
+            unsafe { TerminateJobObject(self.0, 1) };
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+/// A supervisor thread is running.
+static SUPERVISING: AtomicBool = AtomicBool::new(false);
+
+/// Set on exit: the backend going away is expected.
+static STOPPING: AtomicBool = AtomicBool::new(false);
+
+pub fn status() -> BackendStatus {
+    STATUS.lock().map(|s| s.clone()).unwrap_or(BackendStatus {
+        status: Status::Failed,
+        message: None,
+    })
+}
+
+fn set_status(app: &AppHandle, status: Status, message: Option<String>) {
+    match &message {
+        Some(message) => println!("[Backend] {status:?}: {message}"),
+        None => println!("[Backend] {status:?}"),
+    }
+    let payload = BackendStatus { status, message };
+    if let Ok(mut current) = STATUS.lock() {
+        *current = payload.clone();
+    }
+    let _ = app.emit("backend:status", payload);
+}
+
+/// Where the backend is found, and where `uv` should put its virtualenv
+/// (`None` for the project's own `.venv`).
+struct BackendDir {
+    path: PathBuf,
+    venv: Option<PathBuf>,
+}
+
+/// The backend of a checkout, below the working directory or its parent
+/// (`npm run tauri dev` runs in `src-tauri/`); otherwise the copy bundled
+/// with the app, whose install directory may be read-only, so its
+/// virtualenv goes in the app's local data directory.
+fn find_backend_dir(app: &AppHandle) -> Option<BackendDir> {
+    let is_backend = |dir: &Path| dir.join("pyproject.toml").exists();
+    if let Ok(cwd) = std::env::current_dir() {
+        let checkout = [cwd.join("backend"), cwd.join("../backend")]
+            .into_iter()
+            .find(|dir| is_backend(dir));
+        if let Some(path) = checkout {
+            return Some(BackendDir { path, venv: None });
+        }
+    }
+    let path = app.path().resource_dir().ok()?.join("backend");
+    if !is_backend(&path) {
+        return None;
+    }
+    let venv = app
+        .path()
+        .app_local_data_dir()
+        .ok()
+        .map(|dir| dir.join("backend-venv"));
+    Some(BackendDir { path, venv })
+}
+
+/// Forward the backend's output to ours, line by line.
+fn relay(stream: impl Read + Send + 'static, stderr: bool) {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if let Ok(mut last) = LAST_OUTPUT.lock() {
+                *last = Some(Instant::now());
+            }
+            if stderr {
+                eprintln!("[Backend] {line}");
+            } else {
+                println!("[Backend] {line}");
+            }
+        }
+    });
+}
+
+fn spawn(config: &BackendConfig, dir: &BackendDir) -> Result<Backend, AppError> {
+    let connection = super::connection()?;
+    let (program, args) =
+        config
+            .command
+            .split_first()
+            .ok_or_else(|| AppError::BackendProcessFailed {
+                message: "backend.command is empty".into(),
+            })?;
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .current_dir(&dir.path)
+        .env(PORT_ENV, connection.port.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(token) = &connection.token {
+        command.env(TOKEN_ENV, token);
+    }
+    if let Some(venv) = &dir.venv {
+        command.env(VENV_ENV, venv);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        use windows_sys::Win32::System::Threading::CREATE_NO_WINDOW;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    // Take the backend down with us even if we are killed
+    #[cfg(target_os = "linux")]
+    unsafe {
+        use std::os::unix::process::CommandExt;
+        command.pre_exec(|| {
+            libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+            Ok(())
+        });
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| AppError::BackendProcessFailed {
+            message: format!("Failed to run {program}: {e}"),
+        })?;
+    if let Some(stdout) = child.stdout.take() {
+        relay(stdout, false);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        relay(stderr, true);
+    }
+    Ok(Backend::new(child))
+}
+
+/// How the child exited, once it has (or was taken by `stop`).
+fn exited() -> Option<String> {
+    let Ok(mut guard) = CHILD.lock() else {
+        return Some("lost track of the process".into());
+    };
+    let Some(backend) = guard.as_mut() else {
+        return Some("stopped".into());
+    };
+    let exit = match backend.child.try_wait() {
+        Ok(None) => return None,
+        Ok(Some(status)) => status.to_string(),
+        Err(e) => e.to_string(),
+    };
+    *guard = None;
+    Some(exit)
+}
+
+fn is_up() -> bool {
+    super::get::<serde_json::Value>("/health").is_ok()
+}
+
+/// Wait for the child to answer `/health`, then for it to exit, and say
+/// why it did.
+fn watch(app: &AppHandle) -> String {
+    let started = Instant::now();
+    let mut ready = false;
+    loop {
+        if let Some(exit) = exited() {
+            return format!("Backend exited ({exit})");
+        }
+        if !ready {
+            if is_up() {
+                ready = true;
+                set_status(app, Status::Ready, None);
+            } else if quiet_since(started).elapsed() > STARTUP_TIMEOUT {
+                if let Some(backend) = CHILD.lock().ok().and_then(|mut guard| guard.take()) {
+                    backend.kill();
+                }
+                return format!(
+                    "Backend not ready and silent for {}s",
+                    STARTUP_TIMEOUT.as_secs()
+                );
+            }
+        }
+        thread::sleep(HEALTH_INTERVAL);
+    }
+}
+
+/// The later of `started` and the backend's last output.
+fn quiet_since(started: Instant) -> Instant {
+    LAST_OUTPUT
+        .lock()
+        .ok()
+        .and_then(|last| *last)
+        .map_or(started, |last| last.max(started))
+}
+
+fn supervise(app: &AppHandle, config: &BackendConfig) {
+    if !config.managed {
+        // Run by the user (`npm run backend`): only wait for it
+        set_status(app, Status::Starting, None);
+        while !is_up() {
+            thread::sleep(HEALTH_INTERVAL);
+        }
+        set_status(app, Status::Ready, None);
+        return;
+    }
+    let Some(dir) = find_backend_dir(app) else {
+        let message = "backend/ directory not found".to_string();
+        set_status(app, Status::Failed, Some(message));
+        return;
+    };
+
+    let mut crashes = 0;
+    set_status(app, Status::Starting, None);
+    loop {
+        let started = Instant::now();
+        let reason = match spawn(config, &dir) {
+            Ok(backend) => {
+                if let Ok(mut guard) = CHILD.lock() {
+                    *guard = Some(backend);
+                }
+                watch(app)
+            }
+            Err(e) => e.message().to_string(),
+        };
+        if STOPPING.load(Ordering::SeqCst) {
+            return;
+        }
+
+        if started.elapsed() >= STABLE_AFTER {
+            crashes = 0;
+        }
+        crashes += 1;
+        if crashes > MAX_RESTARTS {
+            set_status(app, Status::Failed, Some(reason));
+            return;
+        }
+        let backoff = (INITIAL_BACKOFF * 2u32.pow(crashes - 1)).min(MAX_BACKOFF);
+        let message = format!("{reason}, restarting in {}s", backoff.as_secs());
+        set_status(app, Status::Restarting, Some(message));
+        thread::sleep(backoff);
+    }
+}
+
+/// Start the backend (or wait for it, when it is not managed) and keep it
+/// running until the app exits.
+pub fn start(app: &AppHandle, config: &BackendConfig) {
+    let config = CONFIG.get_or_init(|| config.clone());
+    if SUPERVISING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    thread::spawn(move || {
+        supervise(&app, config);
+        SUPERVISING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Try again after the backend failed.
+pub fn restart(app: &AppHandle) {
+    if status().status != Status::Failed {
+        return;
+    }
+    if let Some(config) = CONFIG.get() {
+        start(app, config);
+    }
+}
+
+/// Stop the backend if the app started it, letting it shut down cleanly
+/// where the platform allows.
+pub fn stop() {
+    STOPPING.store(true, Ordering::SeqCst);
+    let Some(backend) = CHILD.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    println!("[Backend] stopping");
+
+    #[cfg(unix)]
+    let backend = {
+        let mut backend = backend;
+        // uvicorn finishes its shutdown (closing the database) on SIGTERM
+        backend.terminate();
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while Instant::now() < deadline {
+            if !backend.group_alive() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        eprintln!("[Backend] did not exit in time, killing it");
+        backend
+    };
+    backend.kill();
+}
@@ -0,0 +1,271 @@
+//! `openwhisper <command>`: control of the running instance from scripts,
+//! window-manager keybindings and Stream Deck buttons. Commands go over a
+//! Unix socket (`cli/socket.rs`) and answer with the mic state. Elsewhere,
+//! the single-instance plugin hands the arguments over and nothing comes
+//! back, beyond whether an instance was there to take them.
+
+use serde::{Deserialize, Serialize};
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+use crate::backend;
+use crate::mic::{self, MicMode, MicState};
+use crate::shortcuts::{self, emit_with, SessionRequest};
+use crate::tray::show_main_window;
+
+#[cfg(unix)]
+mod socket;
+
+/// Exit code for a command line that could not be parsed.
+const EXIT_USAGE: i32 = 2;
+/// Exit code when no instance is running to carry the command out.
+const EXIT_NOT_RUNNING: i32 = 3;
+
+const USAGE: &str = "\
+Usage: openwhisper [<command>]
+
+Without a command, starts OpenWhisper or brings its window up.
+
+Commands, carried out by the running instance:
+  toggle-dictation                     Start or stop dictation
+  start-transcription [--lang <code>]  Start transcribing, in <code> if given
+  stop                                 Stop the running session
+  status [--json]                      Print the mic state. Exits with 0 if a
+                                       session is running, 1 if idle, 3 if
+                                       OpenWhisper is not running
+  set-language <code>                  Change the language of the next session
+  <action>                             Any shortcut action: toggle-transcription,
+                                       cancel-session, cycle-language, ...";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Bring the main window up (a second launch without arguments).
+    Show,
+    StartTranscription {
+        language: Option<String>,
+    },
+    /// Stop whichever session is running.
+    Stop,
+    Status,
+    SetLanguage {
+        language: String,
+    },
+    /// A bindable action, run as its shortcut would (`toggle-dictation`).
+    Action {
+        key: String,
+    },
+}
+
+/// What the app answers: the mic state it found the command in, or why it
+/// refused it.
+pub type Reply = Result<MicState, String>;
+
+/// Parse a command line (without the program name). The flag is `--json`.
+fn parse(args: &[String]) -> Result<(Command, bool), String> {
+    let Some((name, rest)) = args.split_first() else {
+        return Ok((Command::Show, false));
+    };
+    let mut language = None;
+    let mut json = false;
+    let mut positional = Vec::new();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--lang" => match rest.next() {
+                Some(code) => language = Some(code.clone()),
+                None => return Err("--lang needs a language code".into()),
+            },
+            "--json" => json = true,
+            option if option.starts_with('-') => return Err(format!("unknown option {option}")),
+            value => positional.push(value),
+        }
+    }
+
+    let command = match (name.as_str(), positional.as_slice()) {
+        ("start-transcription", []) => Command::StartTranscription {
+            language: language.take(),
+        },
+        ("stop", []) => Command::Stop,
+        ("status", []) => Command::Status,
+        ("set-language", [code]) => Command::SetLanguage {
+            language: code.to_string(),
+        },
+        ("set-language", _) => return Err("set-language takes one language code".into()),
+        (name, []) if shortcuts::has_action(&name.replace('-', "_")) => Command::Action {
+            key: name.replace('-', "_"),
+        },
+        (name, []) => return Err(format!("unknown command {name}")),
+        (name, _) => return Err(format!("{name} takes no arguments")),
+    };
+    if language.is_some() {
+        return Err("--lang only applies to start-transcription".into());
+    }
+    if json && command != Command::Status {
+        return Err("--json only applies to status".into());
+    }
+    Ok((command, json))
+}
+
+/// Run `openwhisper <command>` against the running instance, returning the
+/// exit code. `None` when the app itself should start.
+pub fn run_command() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        return None;
+    }
+    if matches!(args[0].as_str(), "help" | "--help" | "-h") {
+        println!("{USAGE}");
+        return Some(0);
+    }
+    let (command, json) = match parse(&args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("openwhisper: {message}\n\n{USAGE}");
+            return Some(EXIT_USAGE);
+        }
+    };
+
+    #[cfg(unix)]
+    {
+        Some(socket::send(&command, json))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (command, json);
+        if instance_running() {
+            // Handed over by the single-instance plugin, which then exits
+            None
+        } else {
+            eprintln!("openwhisper: OpenWhisper is not running");
+            Some(EXIT_NOT_RUNNING)
+        }
+    }
+}
+
+/// Whether another instance is up, found by the message window the
+/// single-instance plugin creates (named after the app identifier).
+#[cfg(windows)]
+fn instance_running() -> bool {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::WindowsAndMessaging::FindWindowW;
+
+    let config: serde_json::Value =
+        serde_json::from_str(include_str!("../tauri.conf.json")).unwrap_or_default();
+    let Some(id) = config["identifier"].as_str() else {
+        return false;
+    };
+    let wide = |name: String| -> Vec<u16> {
+        OsStr::new(&name)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    };
+    let class = wide(format!("{id}-sic"));
+    let window = wide(format!("{id}-siw"));
+    let hwnd = unsafe { FindWindowW(class.as_ptr(), window.as_ptr()) };
+    !hwnd.is_null()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn instance_running() -> bool {
+    false
+}
+
+/// Response of `GET /api/languages`, of which only the codes are needed.
+#[derive(Debug, Deserialize)]
+struct Languages {
+    languages: Vec<Language>,
+    auto_detect: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Language {
+    code: String,
+}
+
+fn check_language(code: &str) -> Result<(), String> {
+    let supported =
+        backend::get::<Languages>("/api/languages").map_err(|e| e.message().to_string())?;
+    if code == supported.auto_detect || supported.languages.iter().any(|l| l.code == code) {
+        Ok(())
+    } else {
+        Err(format!("unsupported language {code}"))
+    }
+}
+
+/// Carry out `command` with the same events the shortcuts and the tray
+/// send. The frontend acts on them asynchronously, so the state that comes
+/// back is the one the command found.
+pub fn execute(app: &AppHandle, command: &Command) -> Reply {
+    let mic = mic::current();
+    match command {
+        Command::Show => show_main_window(app),
+        Command::Status => {}
+        Command::Action { key } => shortcuts::run_action(app, key),
+        Command::StartTranscription { language } => match mic.mode {
+            _ if !mic.is_active() => {
+                if let Some(code) = language {
+                    check_language(code)?;
+                }
+                let request = SessionRequest {
+                    language: language.clone(),
+                };
+                emit_with(app, "shortcut:toggle-transcription", request);
+            }
+            MicMode::Transcription => {}
+            _ => return Err("dictation is running".into()),
+        },
+        Command::Stop if mic.is_active() => match mic.mode {
+            MicMode::Dictation => emit_with(app, "shortcut:dictation-stop", ()),
+            MicMode::Transcription => emit_with(app, "shortcut:toggle-transcription", ()),
+            MicMode::None => {}
+        },
+        Command::Stop => {}
+        Command::SetLanguage { language } => {
+            // Same rule as the tray: the language is fixed while a session runs
+            if mic.is_active() {
+                return Err("the language cannot change while a session is running".into());
+            }
+            check_language(language)?;
+            let _ = app.emit("tray:language-changed", language.clone());
+        }
+    }
+    Ok(mic)
+}
+
+/// Listen for commands from `openwhisper <command>`, where that goes over a
+/// Unix socket.
+pub fn listen(app: &AppHandle) {
+    #[cfg(unix)]
+    socket::listen(app);
+    #[cfg(not(unix))]
+    let _ = app;
+}
+
+/// Remove the socket on exit.
+pub fn close() {
+    #[cfg(unix)]
+    socket::close();
+}
+
+/// Carry out what a second launch was asked to do. A bare `openwhisper`
+/// brings the main window up. `args[0]` is the program.
+pub fn forwarded(app: &AppHandle, args: &[String]) {
+    let command = match parse(args.get(1..).unwrap_or_default()) {
+        Ok((command, _)) => command,
+        Err(message) => {
+            eprintln!("[CLI] {message}, showing the main window");
+            Command::Show
+        }
+    };
+    println!("[CLI] forwarded {command:?}");
+    // Checking a language blocks on the backend
+    let app = app.clone();
+    thread::spawn(move || {
+        if let Err(message) = execute(&app, &command) {
+            eprintln!("[CLI] {message}");
+        }
+    });
+}
@@ -0,0 +1,180 @@
+//! The control socket: one JSON [`Command`] per connection, answered with
+//! one JSON [`Reply`].
+
+use std::fs::{self, DirBuilder, Permissions};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::{Command, Reply, EXIT_NOT_RUNNING};
+use crate::mic::{MicPhase, MicState};
+
+/// Exit codes of `openwhisper <command>`, besides 0 and `EXIT_USAGE`.
+const EXIT_IDLE: i32 = 1;
+/// The app refused the command, e.g. an unsupported language.
+const EXIT_REFUSED: i32 = 4;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `$XDG_RUNTIME_DIR`, private to the user by definition.
+fn runtime_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Without a runtime directory: one of the user's own in the temp
+/// directory.
+fn fallback_dir() -> PathBuf {
+    let uid = unsafe { libc::getuid() };
+    std::env::temp_dir().join(format!("openwhisper-{uid}"))
+}
+
+fn path() -> PathBuf {
+    runtime_dir()
+        .unwrap_or_else(fallback_dir)
+        .join("openwhisper.sock")
+}
+
+/// Create `dir` accessible to the user alone, or make sure the one already
+/// there is: the temp directory is shared, and a directory someone else
+/// made would let them reach the socket.
+fn private_dir(dir: &Path) -> io::Result<()> {
+    match DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+    let meta = fs::symlink_metadata(dir)?;
+    let uid = unsafe { libc::getuid() };
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            "not a directory of the user's own with mode 0700",
+        ));
+    }
+    Ok(())
+}
+
+fn serve(app: &AppHandle, stream: UnixStream) {
+    let _ = stream.set_read_timeout(Some(TIMEOUT));
+    let mut line = String::new();
+    if let Err(e) = BufReader::new(&stream).read_line(&mut line) {
+        eprintln!("[CLI] failed to read command: {e}");
+        return;
+    }
+    let reply: Reply = match serde_json::from_str::<Command>(&line) {
+        Ok(command) => {
+            println!("[CLI] {command:?}");
+            super::execute(app, &command)
+        }
+        Err(e) => Err(format!("bad request: {e}")),
+    };
+    let reply = serde_json::to_string(&reply).unwrap_or_default();
+    if let Err(e) = writeln!(&stream, "{reply}") {
+        eprintln!("[CLI] failed to answer: {e}");
+    }
+}
+
+pub fn listen(app: &AppHandle) {
+    if runtime_dir().is_none() {
+        let dir = fallback_dir();
+        if let Err(e) = private_dir(&dir) {
+            eprintln!("[CLI] not listening, {}: {e}", dir.display());
+            return;
+        }
+    }
+    let path = path();
+    // Only one instance runs: a socket already there was left by a crash
+    let _ = fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("[CLI] failed to listen on {}: {e}", path.display());
+            return;
+        }
+    };
+    if let Err(e) = fs::set_permissions(&path, Permissions::from_mode(0o600)) {
+        eprintln!("[CLI] failed to restrict {}: {e}", path.display());
+    }
+    println!("[CLI] listening on {}", path.display());
+
+    let app = app.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let app = app.clone();
+                    thread::spawn(move || serve(&app, stream));
+                }
+                Err(e) => eprintln!("[CLI] failed to accept a connection: {e}"),
+            }
+        }
+    });
+}
+
+pub fn close() {
+    let _ = fs::remove_file(path());
+}
+
+fn request(command: &Command) -> io::Result<Reply> {
+    let mut stream = UnixStream::connect(path())?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    writeln!(stream, "{}", serde_json::to_string(command)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// "idle", or e.g. "dictation recording (fr)".
+fn describe(mic: &MicState) -> String {
+    match mic.state {
+        MicPhase::Idle | MicPhase::Error => mic.state.as_str().to_string(),
+        state => format!(
+            "{} {} ({})",
+            mic.mode.as_str(),
+            state.as_str(),
+            mic.language
+        ),
+    }
+}
+
+/// Send `command` to the running instance and report the outcome; returns
+/// the exit code.
+pub fn send(command: &Command, json: bool) -> i32 {
+    let reply = match request(command) {
+        Ok(reply) => reply,
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+            eprintln!("openwhisper: OpenWhisper is not running");
+            return EXIT_NOT_RUNNING;
+        }
+        Err(e) => {
+            eprintln!("openwhisper: no answer from OpenWhisper: {e}");
+            return EXIT_NOT_RUNNING;
+        }
+    };
+    let mic = match reply {
+        Ok(mic) => mic,
+        Err(message) => {
+            eprintln!("openwhisper: {message}");
+            return EXIT_REFUSED;
+        }
+    };
+    if *command != Command::Status {
+        return 0;
+    }
+    if json {
+        println!("{}", serde_json::to_string(&mic).unwrap_or_default());
+    } else {
+        println!("{}", describe(&mic));
+    }
+    if mic.is_active() {
+        0
+    } else {
+        EXIT_IDLE
+    }
+}
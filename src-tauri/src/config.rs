@@ -0,0 +1,154 @@
+//! Desktop-side view of `config.yaml`.
+//!
+//! The Python backend owns the file and validates it; the Tauri shell only
+//! reads the sections it acts on itself. Unknown keys are ignored and missing
+//! ones fall back to the same defaults as `backend/src/config.py`.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::injection::{default_deny_list, BackendKind, BlockedAction, FocusLock};
+use crate::shortcuts::{DictationMode, TapModifier};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DesktopConfig {
+    pub shortcuts: ShortcutsConfig,
+    pub injection: InjectionConfig,
+    pub backend: BackendConfig,
+}
+
+/// Global shortcut accelerators; an empty string leaves the action unbound.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ShortcutsConfig {
+    pub toggle_dictation: String,
+    pub toggle_transcription: String,
+    pub undo_dictation: String,
+    pub cancel_session: String,
+    pub cycle_language: String,
+    pub toggle_overlay: String,
+    pub open_main_window: String,
+    pub paste_last_transcript: String,
+    pub pause_transcription: String,
+    /// Language code -> shortcut that starts dictation in that language.
+    pub language_dictation: BTreeMap<String, String>,
+    pub dictation_mode: DictationMode,
+    /// Push-to-talk only: shorter presses are ignored.
+    pub push_to_talk_min_hold_ms: u64,
+    /// Modifier to double-tap to toggle dictation; `None` turns it off.
+    pub double_tap_modifier: Option<TapModifier>,
+    /// Longest tap, and longest gap between the two taps.
+    pub double_tap_interval_ms: u64,
+}
+
+impl Default for ShortcutsConfig {
+    fn default() -> Self {
+        Self {
+            toggle_dictation: "Ctrl+Shift+D".into(),
+            toggle_transcription: "Ctrl+Shift+T".into(),
+            undo_dictation: String::new(),
+            cancel_session: String::new(),
+            cycle_language: String::new(),
+            toggle_overlay: String::new(),
+            open_main_window: String::new(),
+            paste_last_transcript: String::new(),
+            pause_transcription: String::new(),
+            language_dictation: BTreeMap::new(),
+            dictation_mode: DictationMode::default(),
+            push_to_talk_min_hold_ms: 250,
+            double_tap_modifier: None,
+            double_tap_interval_ms: 300,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct InjectionConfig {
+    pub backend: BackendKind,
+    pub focus_lock: FocusLock,
+    pub deny_list: Vec<String>,
+    pub allow_list: Vec<String>,
+    pub blocked_action: BlockedAction,
+}
+
+impl Default for InjectionConfig {
+    fn default() -> Self {
+        Self {
+            backend: BackendKind::default(),
+            focus_lock: FocusLock::default(),
+            deny_list: default_deny_list(),
+            allow_list: Vec::new(),
+            blocked_action: BlockedAction::default(),
+        }
+    }
+}
+
+/// Where the Python backend listens, and how to run it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BackendConfig {
+    pub host: String,
+    pub port: u16,
+    /// Start the backend with the app and restart it if it crashes.
+    pub managed: bool,
+    /// Program and arguments, run from the `backend/` directory.
+    pub command: Vec<String>,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 8001,
+            managed: true,
+            command: ["uv", "run", "python", "-m", "src.main"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+/// Same lookup order as the backend's `find_config_path`: project root
+/// first, then the working directory and its parent (`src-tauri/` in dev).
+/// Release builds skip the project root: it is wherever the binary was
+/// built, not where it runs.
+fn find_config_path() -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    #[cfg(debug_assertions)]
+    candidates.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../config.yaml"));
+    if let Ok(cwd) = std::env::current_dir() {
+        candidates.push(cwd.join("config.yaml"));
+        candidates.push(cwd.join("../config.yaml"));
+    }
+    candidates.into_iter().find(|p| p.exists())
+}
+
+/// Load `config.yaml`, falling back to defaults if it is missing or invalid.
+pub fn load_config() -> DesktopConfig {
+    let Some(path) = find_config_path() else {
+        println!("[Config] config.yaml not found, using defaults");
+        return DesktopConfig::default();
+    };
+
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) => {
+            eprintln!("[Config] failed to read {}: {e}", path.display());
+            return DesktopConfig::default();
+        }
+    };
+
+    match serde_yaml::from_str::<Option<DesktopConfig>>(&raw) {
+        Ok(config) => {
+            println!("[Config] loaded {}", path.display());
+            config.unwrap_or_default()
+        }
+        Err(e) => {
+            eprintln!("[Config] invalid {}: {e}, using defaults", path.display());
+            DesktopConfig::default()
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! `org.openwhisper.Control` on the session bus, for GNOME extensions, KDE
+//! widgets and `busctl` scripts. Its methods are the commands of
+//! `openwhisper <command>`, so they send the same events as the shortcuts
+//! and the tray; `StateChanged` follows `mic-state-changed`.
+
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{App, Listener};
+use zbus::blocking::{connection, Connection};
+use zbus::object_server::SignalEmitter;
+use zbus::{fdo, interface};
+
+use crate::cli::{self, Command, Reply};
+use crate::mic::MicState;
+
+const NAME: &str = "org.openwhisper.Control";
+const PATH: &str = "/org/openwhisper/Control";
+
+/// Carries out a command: `cli::execute` against the app.
+type Execute = Arc<dyn Fn(&Command) -> Reply + Send + Sync>;
+
+struct Control {
+    execute: Execute,
+}
+
+impl Control {
+    /// Commands may wait on the backend (checking a language), so they run
+    /// off the bus executor.
+    async fn run(&self, command: Command) -> fdo::Result<MicState> {
+        println!("[DBus] {command:?}");
+        let execute = self.execute.clone();
+        tauri::async_runtime::spawn_blocking(move || execute(&command))
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?
+            .map_err(fdo::Error::Failed)
+    }
+}
+
+#[interface(name = "org.openwhisper.Control")]
+impl Control {
+    async fn toggle_dictation(&self) -> fdo::Result<()> {
+        let key = "toggle_dictation".into();
+        self.run(Command::Action { key }).await.map(drop)
+    }
+
+    async fn toggle_transcription(&self) -> fdo::Result<()> {
+        let key = "toggle_transcription".into();
+        self.run(Command::Action { key }).await.map(drop)
+    }
+
+    /// Fails while a session is running, or for an unsupported language.
+    async fn set_language(&self, language: String) -> fdo::Result<()> {
+        self.run(Command::SetLanguage { language }).await.map(drop)
+    }
+
+    /// State ("recording"), mode ("dictation") and language, as in
+    /// `openwhisper status --json`.
+    #[zbus(out_args("state", "mode", "language"))]
+    async fn get_state(&self) -> fdo::Result<(String, String, String)> {
+        let mic = self.run(Command::Status).await?;
+        Ok((
+            mic.state.as_str().into(),
+            mic.mode.as_str().into(),
+            mic.language,
+        ))
+    }
+
+    #[zbus(signal)]
+    async fn state_changed(
+        emitter: &SignalEmitter<'_>,
+        state: &str,
+        mode: &str,
+        language: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Keeps the name for as long as the app runs.
+static CONNECTION: OnceLock<Connection> = OnceLock::new();
+
+fn serve(bus: connection::Builder, execute: Execute) -> zbus::Result<Connection> {
+    bus.name(NAME)?.serve_at(PATH, Control { execute })?.build()
+}
+
+/// Publish the service, and signal every change of the mic state on it.
+pub fn start(app: &App) {
+    let handle = app.handle().clone();
+    let execute: Execute = Arc::new(move |command| cli::execute(&handle, command));
+    let connection = match connection::Builder::session().and_then(|bus| serve(bus, execute)) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("[DBus] failed to publish {NAME}: {e}");
+            return;
+        }
+    };
+    let emitter = match connection.object_server().interface::<_, Control>(PATH) {
+        Ok(control) => control.signal_emitter().clone(),
+        Err(e) => {
+            eprintln!("[DBus] {NAME} not served: {e}");
+            return;
+        }
+    };
+    println!("[DBus] {NAME} published");
+
+    // The frontend reports the state again whenever anything it tracks
+    // changes: only signal actual changes
+    let last = Mutex::new(None::<MicState>);
+    app.listen("mic-state-changed", move |event| {
+        let Ok(mic) = serde_json::from_str::<MicState>(event.payload()) else {
+            return;
+        };
+        let Ok(mut last) = last.lock() else {
+            return;
+        };
+        if last.as_ref() == Some(&mic) {
+            return;
+        }
+        *last = Some(mic.clone());
+
+        let emitter = emitter.clone();
+        tauri::async_runtime::spawn(async move {
+            let (state, mode) = (mic.state.as_str(), mic.mode.as_str());
+            if let Err(e) = Control::state_changed(&emitter, state, mode, &mic.language).await {
+                eprintln!("[DBus] failed to signal StateChanged: {e}");
+            }
+        });
+    });
+    let _ = CONNECTION.set(connection);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mic::{MicMode, MicPhase};
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command as Process, Stdio};
+
+    /// A bus of the test's own, killed when dropped.
+    struct Bus(Child);
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    /// Start a private `dbus-daemon` and return it with its address, or
+    /// `None` where it is not installed.
+    fn private_bus() -> Option<(Bus, String)> {
+        let mut child = Process::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+        let stdout = child.stdout.take()?;
+        let bus = Bus(child);
+        let mut address = String::new();
+        BufReader::new(stdout).read_line(&mut address).ok()?;
+        Some((bus, address.trim().to_string()))
+    }
+
+    fn fake_execute(command: &Command) -> Reply {
+        match command {
+            Command::SetLanguage { language } => Err(format!("unsupported language {language}")),
+            _ => Ok(MicState {
+                state: MicPhase::Recording,
+                mode: MicMode::Dictation,
+                language: "fr".into(),
+            }),
+        }
+    }
+
+    #[test]
+    fn serves_control_interface() {
+        let Some((_bus, address)) = private_bus() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let bus = connection::Builder::address(address.as_str()).unwrap();
+        let _server = serve(bus, Arc::new(fake_execute)).unwrap();
+        let client = connection::Builder::address(address.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let reply = client
+            .call_method(Some(NAME), PATH, Some(NAME), "GetState", &())
+            .unwrap();
+        let state: (String, String, String) = reply.body().deserialize().unwrap();
+        assert_eq!(state, ("recording".into(), "dictation".into(), "fr".into()));
+
+        let refused = client
+            .call_method(Some(NAME), PATH, Some(NAME), "SetLanguage", &("xx",))
+            .unwrap_err();
+        match refused {
+            zbus::Error::MethodError(name, message, _) => {
+                assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.Failed");
+                assert_eq!(message.as_deref(), Some("unsupported language xx"));
+            }
+            other => panic!("unexpected error {other:?}"),
+        }
+    }
+}
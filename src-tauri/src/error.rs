@@ -0,0 +1,128 @@
+//! Error type returned by every Tauri command.
+//!
+//! Serialized as `{ "code": "...", "message": "...", ...detail }` so the
+//! frontend can branch on `code` (retry, fall back to another backend, show
+//! a help dialog) instead of parsing messages. Codes are part of the IPC
+//! contract: add new ones, never rename existing ones.
+
+use serde::Serialize;
+use std::fmt;
+use std::sync::PoisonError;
+
+use crate::injection::{BackendKind, BlockReason};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "code")]
+pub enum AppError {
+    /// The clipboard could not be opened, read or written.
+    ClipboardUnavailable { message: String },
+    /// Key events could not be sent, or an input helper (`wtype`,
+    /// `ydotool`) failed. Often transient; another backend may work.
+    InputSimulationFailed { message: String },
+    /// A lock was poisoned by a panic on another thread. Needs a restart.
+    MutexPoisoned { message: String },
+    /// No display server to talk to (no X server, or no access to it).
+    NoDisplay { message: String },
+    /// The requested injection backend cannot run here: wrong platform, or
+    /// the helper tools it needs are not installed.
+    BackendUnavailable {
+        backend: BackendKind,
+        message: String,
+    },
+    /// Focus is not on the window the dictation session is locked to.
+    TargetWindowChanged { message: String },
+    /// The focused app is refused by the injection deny/allow-list.
+    InjectionBlocked {
+        reason: BlockReason,
+        app: Option<String>,
+        message: String,
+    },
+    /// The injection was cancelled before it ran.
+    InjectionCancelled { message: String },
+    /// The injection worker thread is gone.
+    QueueStopped { message: String },
+    /// A window operation (e.g. dragging the overlay) failed.
+    WindowOperationFailed { message: String },
+    /// A key combination could not be recorded: the keyboard is grabbed by
+    /// another app, the platform does not allow it, or a recording is
+    /// already running.
+    ShortcutCaptureFailed { message: String },
+    /// A request to the Python backend's HTTP API failed (not running yet,
+    /// or an error response).
+    ApiRequestFailed { message: String },
+    /// The backend process could not be started.
+    BackendProcessFailed { message: String },
+}
+
+impl AppError {
+    pub fn clipboard(message: impl Into<String>) -> Self {
+        Self::ClipboardUnavailable {
+            message: message.into(),
+        }
+    }
+
+    pub fn input(message: impl Into<String>) -> Self {
+        Self::InputSimulationFailed {
+            message: message.into(),
+        }
+    }
+
+    pub fn no_display(message: impl Into<String>) -> Self {
+        Self::NoDisplay {
+            message: message.into(),
+        }
+    }
+
+    pub fn backend_unavailable(backend: BackendKind, message: impl Into<String>) -> Self {
+        Self::BackendUnavailable {
+            backend,
+            message: message.into(),
+        }
+    }
+
+    pub fn cancelled() -> Self {
+        Self::InjectionCancelled {
+            message: "Injection cancelled".into(),
+        }
+    }
+
+    pub fn queue_stopped() -> Self {
+        Self::QueueStopped {
+            message: "Injection worker stopped".into(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::ClipboardUnavailable { message }
+            | Self::InputSimulationFailed { message }
+            | Self::MutexPoisoned { message }
+            | Self::NoDisplay { message }
+            | Self::BackendUnavailable { message, .. }
+            | Self::TargetWindowChanged { message }
+            | Self::InjectionBlocked { message, .. }
+            | Self::InjectionCancelled { message }
+            | Self::QueueStopped { message }
+            | Self::WindowOperationFailed { message }
+            | Self::ShortcutCaptureFailed { message }
+            | Self::ApiRequestFailed { message }
+            | Self::BackendProcessFailed { message } => message,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl<T> From<PoisonError<T>> for AppError {
+    fn from(e: PoisonError<T>) -> Self {
+        Self::MutexPoisoned {
+            message: format!("Mutex poisoned: {e}"),
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Identity of the window that currently has keyboard focus, so injected
+//! text can be tied to the window it went into.
+
+use serde::Serialize;
+#[cfg(any(windows, target_os = "linux"))]
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// Opaque, platform-specific window handle (HWND on Windows, X11 window id
+/// on Linux). Only meaningful for equality checks within one run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct WindowId(u64);
+
+/// What the application owning a window is called, for matching against
+/// the injection deny and allow lists.
+#[derive(Debug, Default, Clone)]
+pub struct AppIdentity {
+    /// Window class names (both halves of X11 `WM_CLASS`, Win32 class).
+    pub classes: Vec<String>,
+    /// Executable name of the owning process.
+    pub process: Option<String>,
+    /// The owning process, to look up what runs under it.
+    #[cfg(target_os = "linux")]
+    pid: Option<u32>,
+    /// Processes running under it in the foreground of their terminal
+    /// (Linux only), e.g. `sudo` waiting for a password in a terminal.
+    /// Filled in by `read_descendants`.
+    pub descendants: Vec<String>,
+}
+
+impl AppIdentity {
+    /// Read what runs under the window now: unlike its class and process,
+    /// that changes while the window stays (Linux only).
+    pub fn read_descendants(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            self.descendants = self
+                .pid
+                .map(procfs::foreground_descendant_comms)
+                .unwrap_or_default();
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.classes
+            .iter()
+            .chain(&self.process)
+            .chain(&self.descendants)
+            .map(String::as_str)
+    }
+}
+
+/// The focused window, or `None` if it cannot be determined (e.g. on
+/// Wayland, which does not expose other clients' windows).
+#[cfg(windows)]
+pub fn focused_window() -> Option<WindowId> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    (!hwnd.is_null()).then_some(WindowId(hwnd as u64))
+}
+
+#[cfg(target_os = "linux")]
+pub fn focused_window() -> Option<WindowId> {
+    if crate::injection::is_wayland_session() {
+        return None;
+    }
+    x11::active_window().map(|id| WindowId(id.into()))
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn focused_window() -> Option<WindowId> {
+    None
+}
+
+/// Ask the OS to give `window` keyboard focus. Callers should check
+/// `focused_window` afterwards: window managers may refuse.
+#[cfg(windows)]
+pub fn focus_window(window: WindowId) -> Result<(), AppError> {
+    use windows_sys::Win32::Foundation::HWND;
+    use windows_sys::Win32::UI::WindowsAndMessaging::SetForegroundWindow;
+
+    let ok = unsafe { SetForegroundWindow(window.0 as HWND) };
+    if ok != 0 {
+        Ok(())
+    } else {
+        Err(AppError::WindowOperationFailed {
+            message: "SetForegroundWindow refused".into(),
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn focus_window(window: WindowId) -> Result<(), AppError> {
+    x11::activate_window(window.0 as u32)
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn focus_window(_window: WindowId) -> Result<(), AppError> {
+    Err(AppError::WindowOperationFailed {
+        message: "Refocusing windows is not supported on this platform".into(),
+    })
+}
+
+#[cfg(windows)]
+pub fn app_identity(window: WindowId) -> AppIdentity {
+    use windows_sys::Win32::Foundation::{CloseHandle, HWND};
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetClassNameW, GetWindowThreadProcessId};
+
+    let hwnd = window.0 as HWND;
+    let mut identity = AppIdentity::default();
+    let mut buf = [0u16; 260];
+
+    let len = unsafe { GetClassNameW(hwnd, buf.as_mut_ptr(), buf.len() as i32) };
+    if len > 0 {
+        identity
+            .classes
+            .push(String::from_utf16_lossy(&buf[..len as usize]));
+    }
+
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+    if pid == 0 {
+        return identity;
+    }
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if process.is_null() {
+        return identity;
+    }
+    let mut size = buf.len() as u32;
+    let ok = unsafe {
+        QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buf.as_mut_ptr(), &mut size)
+    };
+    if ok != 0 {
+        let path = String::from_utf16_lossy(&buf[..size as usize]);
+        identity.process = Path::new(&path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
+    }
+    unsafe { CloseHandle(process) };
+    identity
+}
+
+#[cfg(target_os = "linux")]
+pub fn app_identity(window: WindowId) -> AppIdentity {
+    let (classes, pid) = x11::class_and_pid(window.0 as u32);
+    AppIdentity {
+        classes,
+        process: pid.and_then(procfs::comm),
+        pid,
+        descendants: Vec::new(),
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn app_identity(_window: WindowId) -> AppIdentity {
+    AppIdentity::default()
+}
+
+#[cfg(target_os = "linux")]
+mod procfs {
+    use super::Path;
+    use std::collections::HashMap;
+    use std::fs;
+
+    pub fn comm(pid: u32) -> Option<String> {
+        let comm = fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+        Some(comm.trim_end().to_string())
+    }
+
+    struct Stat {
+        pid: u32,
+        ppid: u32,
+        comm: String,
+        /// In the foreground process group of its controlling terminal.
+        foreground: bool,
+    }
+
+    /// Parse `/proc/<pid>/stat`. The command name is in parentheses and may
+    /// itself contain spaces or parentheses.
+    fn stat(path: &Path) -> Option<Stat> {
+        let stat = fs::read_to_string(path.join("stat")).ok()?;
+        let (head, tail) = stat.rsplit_once(')')?;
+        let (pid, comm) = head.split_once(" (")?;
+        // state ppid pgrp session tty_nr tpgid ...
+        let fields: Vec<&str> = tail.split_whitespace().take(6).collect();
+        let [_, ppid, pgrp, _, _, tpgid] = fields[..] else {
+            return None;
+        };
+        let tpgid: i32 = tpgid.parse().ok()?;
+        Some(Stat {
+            pid: pid.parse().ok()?,
+            ppid: ppid.parse().ok()?,
+            comm: comm.to_string(),
+            foreground: tpgid > 0 && pgrp.parse::<i32>().ok()? == tpgid,
+        })
+    }
+
+    /// Names of the processes below `root` in the process tree that are in
+    /// the foreground of their terminal: what a terminal window is running
+    /// right now, not its background jobs. A terminal's tabs all count, as
+    /// X11 cannot tell which one is showing.
+    pub fn foreground_descendant_comms(root: u32) -> Vec<String> {
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+        let mut children: HashMap<u32, Vec<Stat>> = HashMap::new();
+        for entry in entries.flatten() {
+            if let Some(stat) = stat(&entry.path()) {
+                children.entry(stat.ppid).or_default().push(stat);
+            }
+        }
+
+        let mut names = Vec::new();
+        let mut stack = vec![root];
+        while let Some(pid) = stack.pop() {
+            for child in children.remove(&pid).unwrap_or_default() {
+                stack.push(child.pid);
+                if child.foreground {
+                    names.push(child.comm);
+                }
+            }
+        }
+        names
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::sync::Mutex;
+    use x11rb::connection::Connection;
+
+    use crate::error::AppError;
+    use x11rb::protocol::xproto::{
+        Atom, AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, Window,
+    };
+    use x11rb::rust_connection::RustConnection;
+
+    struct X11 {
+        conn: RustConnection,
+        root: Window,
+        net_active_window: Atom,
+        net_wm_pid: Atom,
+    }
+
+    /// Kept open between calls; dropped and reopened after any error.
+    static X11_CONN: Mutex<Option<X11>> = Mutex::new(None);
+
+    fn connect() -> Option<X11> {
+        let (conn, screen) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots[screen].root;
+        let net_active_window = conn
+            .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+            .ok()?
+            .reply()
+            .ok()?
+            .atom;
+        let net_wm_pid = conn
+            .intern_atom(false, b"_NET_WM_PID")
+            .ok()?
+            .reply()
+            .ok()?
+            .atom;
+        Some(X11 {
+            conn,
+            root,
+            net_active_window,
+            net_wm_pid,
+        })
+    }
+
+    fn query(x11: &X11) -> Option<Option<Window>> {
+        let reply = x11
+            .conn
+            .get_property(
+                false,
+                x11.root,
+                x11.net_active_window,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(
+            reply
+                .value32()
+                .and_then(|mut v| v.next())
+                .filter(|&w| w != 0),
+        )
+    }
+
+    /// `_NET_ACTIVE_WINDOW` as maintained by the window manager.
+    pub fn active_window() -> Option<Window> {
+        let mut guard = X11_CONN.lock().ok()?;
+        if guard.is_none() {
+            *guard = connect();
+        }
+        match query(guard.as_ref()?) {
+            Some(window) => window,
+            None => {
+                *guard = None;
+                None
+            }
+        }
+    }
+
+    /// Both halves of `WM_CLASS` (instance, class) and `_NET_WM_PID`.
+    pub fn class_and_pid(window: Window) -> (Vec<String>, Option<u32>) {
+        let Ok(mut guard) = X11_CONN.lock() else {
+            return (Vec::new(), None);
+        };
+        if guard.is_none() {
+            *guard = connect();
+        }
+        let Some(x11) = guard.as_ref() else {
+            return (Vec::new(), None);
+        };
+
+        let classes = x11
+            .conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| {
+                reply
+                    .value
+                    .split(|&b| b == 0)
+                    .filter(|part| !part.is_empty())
+                    .map(|part| String::from_utf8_lossy(part).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let pid = x11
+            .conn
+            .get_property(false, window, x11.net_wm_pid, AtomEnum::CARDINAL, 0, 1)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().and_then(|mut v| v.next()));
+        (classes, pid)
+    }
+
+    /// Request activation through `_NET_ACTIVE_WINDOW`, the way pagers and
+    /// taskbars do (source indication 2), which focus-stealing prevention
+    /// lets through.
+    pub fn activate_window(window: Window) -> Result<(), AppError> {
+        let mut guard = X11_CONN.lock()?;
+        if guard.is_none() {
+            *guard = connect();
+        }
+        let x11 = guard
+            .as_ref()
+            .ok_or_else(|| AppError::no_display("Failed to connect to X server"))?;
+        let event = ClientMessageEvent::new(32, window, x11.net_active_window, [2, 0, 0, 0, 0]);
+        let result = x11
+            .conn
+            .send_event(
+                false,
+                x11.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .map_err(|e| AppError::no_display(format!("Failed to activate window: {e}")))
+            .and_then(|_| {
+                x11.conn
+                    .flush()
+                    .map_err(|e| AppError::no_display(format!("X connection lost: {e}")))
+            });
+        if result.is_err() {
+            *guard = None;
+        }
+        result
+    }
+}
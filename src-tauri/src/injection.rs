@@ -1,50 +1,340 @@
-use arboard::Clipboard;
+mod clipboard;
+mod policy;
+mod queue;
+mod recording;
+mod spans;
+mod typing;
+#[cfg(target_os = "linux")]
+mod wayland;
+#[cfg(target_os = "linux")]
+mod xtest;
+
 use enigo::{Direction, Enigo, Key, Keyboard, Settings};
-use std::sync::Mutex;
-use std::thread;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use crate::config::InjectionConfig;
+use crate::error::AppError;
+
+pub use clipboard::copy_to_clipboard;
+use policy::InjectionPolicy;
+pub use policy::{default_deny_list, BlockReason, BlockedAction};
+use queue::Edit;
+pub use queue::InjectionQueue;
+use spans::grapheme_len;
+#[cfg(target_os = "linux")]
+pub use wayland::is_wayland_session;
 
 static ENIGO: Mutex<Option<Enigo>> = Mutex::new(None);
 
-fn get_enigo() -> Result<std::sync::MutexGuard<'static, Option<Enigo>>, String> {
-    let mut guard = ENIGO.lock().map_err(|e| format!("Mutex poisoned: {e}"))?;
+/// How long after the worker stops typing its keys may still be reported
+/// to the double-tap listener.
+const TYPING_ECHO: Duration = Duration::from_millis(150);
+
+/// Set while the worker sends synthetic input.
+static TYPING: AtomicBool = AtomicBool::new(false);
+/// When the worker last stopped sending synthetic input.
+static TYPED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// The backend every `inject_text` call goes through.
+static BACKEND: Mutex<Option<Box<dyn InjectionBackend>>> = Mutex::new(None);
+
+fn get_enigo() -> Result<std::sync::MutexGuard<'static, Option<Enigo>>, AppError> {
+    let mut guard = ENIGO.lock()?;
     if guard.is_none() {
+        // Enigo only fails to start when it cannot reach the display server
         *guard = Some(
-            Enigo::new(&Settings::default()).map_err(|e| format!("Failed to init enigo: {e}"))?,
+            Enigo::new(&Settings::default())
+                .map_err(|e| AppError::no_display(format!("Failed to init enigo: {e}")))?,
         );
     }
     Ok(guard)
 }
 
-#[tauri::command]
-pub fn inject_text(text: &str) -> Result<(), String> {
-    println!("[injection] inject_text called with: {:?}", text);
+/// A strategy for getting text into the focused application.
+pub trait InjectionBackend: Send {
+    fn kind(&self) -> BackendKind;
+    fn inject(&mut self, text: &str) -> Result<(), AppError>;
 
-    // Set clipboard content
-    let mut clipboard =
-        Clipboard::new().map_err(|e| format!("Failed to open clipboard: {e}"))?;
-    clipboard
-        .set_text(text)
-        .map_err(|e| format!("Failed to set clipboard: {e}"))?;
+    /// Delete the `count` graphemes before the caret.
+    fn erase(&mut self, count: usize) -> Result<(), AppError> {
+        let mut guard = get_enigo()?;
+        let enigo = guard.as_mut().unwrap();
+        for _ in 0..count {
+            enigo
+                .key(Key::Backspace, Direction::Click)
+                .map_err(|e| AppError::input(format!("Backspace failed: {e}")))?;
+        }
+        Ok(())
+    }
+}
 
-    // Small delay to let clipboard update propagate
-    thread::sleep(Duration::from_millis(5));
+/// Selectable backends, as spelled in `config.yaml` (`injection.backend`)
+/// and in the `set_injection_backend` command.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    /// Pick per session: Wayland helpers on a Wayland session, clipboard
+    /// paste everywhere else. Only ever a request; never reported as active.
+    #[default]
+    Auto,
+    /// Put the text on the clipboard and simulate Ctrl+V.
+    Clipboard,
+    /// Type the text as Unicode key events; leaves the clipboard alone.
+    Unicode,
+    /// Type the text through the X11 XTest extension (Linux/X11 only).
+    Xtest,
+    /// Type or paste through Wayland helpers (`wtype`, `ydotool`).
+    Wayland,
+    /// Record the text without touching the system (for tests).
+    Recording,
+}
 
-    // Simulate Ctrl+V to paste
-    let mut guard = get_enigo()?;
-    let enigo = guard.as_mut().unwrap();
-    enigo
-        .key(Key::Control, Direction::Press)
-        .map_err(|e| format!("Ctrl press failed: {e}"))?;
-    enigo
-        .key(Key::Unicode('v'), Direction::Click)
-        .map_err(|e| format!("V click failed: {e}"))?;
-    enigo
-        .key(Key::Control, Direction::Release)
-        .map_err(|e| format!("Ctrl release failed: {e}"))?;
+/// What to do when the window dictation started in loses focus
+/// (`injection.focus_lock` in `config.yaml`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusLock {
+    /// Type into whatever window has focus.
+    Off,
+    /// Bring the original window back to the front, then type.
+    Refocus,
+    /// Hold the text until the original window has focus again.
+    #[default]
+    Pause,
+}
 
-    // Small delay to let the paste complete before next injection
-    thread::sleep(Duration::from_millis(10));
+impl BackendKind {
+    fn create(self) -> Result<Box<dyn InjectionBackend>, AppError> {
+        Ok(match self {
+            #[cfg(target_os = "linux")]
+            BackendKind::Auto if wayland::is_wayland_session() => {
+                Box::new(wayland::WaylandBackend::detect())
+            }
+            BackendKind::Auto => Box::new(clipboard::ClipboardPasteBackend),
+            BackendKind::Clipboard => Box::new(clipboard::ClipboardPasteBackend),
+            BackendKind::Unicode => Box::new(typing::UnicodeBackend),
+            #[cfg(target_os = "linux")]
+            BackendKind::Xtest => Box::new(xtest::XTestBackend::connect()?),
+            #[cfg(not(target_os = "linux"))]
+            BackendKind::Xtest => {
+                return Err(AppError::backend_unavailable(
+                    self,
+                    "XTest backend is only available on Linux",
+                ))
+            }
+            #[cfg(target_os = "linux")]
+            BackendKind::Wayland => Box::new(wayland::WaylandBackend::detect()),
+            #[cfg(not(target_os = "linux"))]
+            BackendKind::Wayland => {
+                return Err(AppError::backend_unavailable(
+                    self,
+                    "Wayland backend is only available on Linux",
+                ))
+            }
+            BackendKind::Recording => Box::new(recording::RecordingBackend),
+        })
+    }
+}
 
+fn set_backend(kind: BackendKind) -> Result<(), AppError> {
+    let backend = kind.create()?;
+    let mut guard = BACKEND.lock()?;
+    println!(
+        "[injection] backend set to {:?} (requested {kind:?})",
+        backend.kind()
+    );
+    *guard = Some(backend);
     Ok(())
 }
+
+/// Select the configured backend at startup, falling back to automatic
+/// selection if it cannot be created (e.g. XTest without an X server).
+pub fn init(config: &InjectionConfig) {
+    if let Err(e) = set_backend(config.backend) {
+        eprintln!(
+            "[injection] {:?} backend unavailable ({e}), selecting automatically",
+            config.backend
+        );
+        let _ = set_backend(BackendKind::Auto);
+    }
+}
+
+/// Inject right away on the calling thread. Only the queue worker calls
+/// this, so injections never interleave.
+/// Dictated text never goes to the log, only how much of it there is.
+fn inject_now(text: &str) -> Result<(), AppError> {
+    let mut guard = BACKEND.lock()?;
+    if guard.is_none() {
+        *guard = Some(BackendKind::Auto.create()?);
+    }
+    let backend = guard.as_mut().unwrap();
+    println!(
+        "[injection] injecting {} grapheme(s) via {:?}",
+        grapheme_len(text),
+        backend.kind()
+    );
+    while_typing(|| backend.inject(text))
+}
+
+/// Erase `count` graphemes, then type `text`. Worker thread only, like
+/// `inject_now`.
+fn replace_now(count: usize, text: &str) -> Result<(), AppError> {
+    let mut guard = BACKEND.lock()?;
+    if guard.is_none() {
+        *guard = Some(BackendKind::Auto.create()?);
+    }
+    let backend = guard.as_mut().unwrap();
+    println!(
+        "[injection] erasing {count}, then injecting {} grapheme(s) via {:?}",
+        grapheme_len(text),
+        backend.kind()
+    );
+    while_typing(|| {
+        if count > 0 {
+            backend.erase(count)?;
+        }
+        if !text.is_empty() {
+            backend.inject(text)?;
+        }
+        Ok(())
+    })
+}
+
+fn while_typing<T>(send: impl FnOnce() -> T) -> T {
+    TYPING.store(true, Ordering::SeqCst);
+    let result = send();
+    if let Ok(mut typed_at) = TYPED_AT.lock() {
+        *typed_at = Some(Instant::now());
+    }
+    TYPING.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Whether key events seen right now may be our own synthetic ones. The
+/// X11 RECORD extension reports XTest and enigo keys like real ones.
+pub fn is_typing() -> bool {
+    TYPING.load(Ordering::SeqCst)
+        || TYPED_AT
+            .lock()
+            .ok()
+            .and_then(|typed_at| *typed_at)
+            .is_some_and(|t| t.elapsed() < TYPING_ECHO)
+}
+
+/// Queue `text` and wait until it has been injected.
+#[tauri::command]
+pub async fn inject_text(text: String, queue: State<'_, InjectionQueue>) -> Result<(), AppError> {
+    let (done_tx, done_rx) = mpsc::channel();
+    queue.enqueue(Edit::Append(text), Some(done_tx))?;
+    tauri::async_runtime::spawn_blocking(move || done_rx.recv())
+        .await
+        .map_err(|_| AppError::queue_stopped())?
+        .map_err(|_| AppError::queue_stopped())?
+}
+
+/// Queue `text` behind any pending injections and return its job id.
+/// Progress is reported through `injection:progress` and
+/// `injection:completed` events.
+#[tauri::command]
+pub fn enqueue_injection(text: String, queue: State<'_, InjectionQueue>) -> Result<u64, AppError> {
+    queue.enqueue(Edit::Append(text), None)
+}
+
+/// The dictation session is over (ended, failed or aborted).
+#[tauri::command]
+pub fn end_injection_session(queue: State<'_, InjectionQueue>) {
+    queue.end_session();
+}
+
+/// Queue a correction: erase the last `count` injected graphemes (capped at
+/// what the current dictation session typed), then type `text`.
+#[tauri::command]
+pub fn replace_injected_text(
+    count: usize,
+    text: String,
+    queue: State<'_, InjectionQueue>,
+) -> Result<u64, AppError> {
+    queue.enqueue(Edit::Replace { erase: count, text }, None)
+}
+
+/// Start tracking injected spans for a dictation session.
+#[tauri::command]
+pub fn begin_injection_session(
+    session_id: i64,
+    queue: State<'_, InjectionQueue>,
+) -> Result<(), AppError> {
+    queue.begin_session(session_id)
+}
+
+/// Queue the final text of a segment; the provisional deltas typed for it
+/// are corrected in place, rewriting only what differs.
+#[tauri::command]
+pub fn finalize_injected_segment(
+    text: String,
+    queue: State<'_, InjectionQueue>,
+) -> Result<u64, AppError> {
+    queue.enqueue(Edit::Finalize(text), None)
+}
+
+/// Wait until every injection queued so far has completed.
+#[tauri::command]
+pub async fn flush_injection(queue: State<'_, InjectionQueue>) -> Result<(), AppError> {
+    let ack = queue.flush()?;
+    tauri::async_runtime::spawn_blocking(move || ack.recv())
+        .await
+        .map_err(|_| AppError::queue_stopped())?
+        .map_err(|_| AppError::queue_stopped())
+}
+
+/// Drop every queued injection that has not started yet.
+#[tauri::command]
+pub fn cancel_injection(queue: State<'_, InjectionQueue>) -> usize {
+    let pending = queue.cancel();
+    println!("[injection] cancelled, {pending} job(s) outstanding");
+    pending
+}
+
+#[tauri::command]
+pub fn get_injection_backend() -> Result<BackendKind, AppError> {
+    let guard = BACKEND.lock()?;
+    Ok(guard.as_ref().map_or(BackendKind::Auto, |b| b.kind()))
+}
+
+#[tauri::command]
+pub fn set_injection_backend(backend: BackendKind) -> Result<(), AppError> {
+    set_backend(backend)
+}
+
+#[tauri::command]
+pub fn set_focus_lock(mode: FocusLock, queue: State<'_, InjectionQueue>) -> Result<(), AppError> {
+    println!("[injection] focus lock set to {mode:?}");
+    queue.set_focus_lock(mode)
+}
+
+/// Replace the deny-list, allow-list and blocked action at runtime.
+#[tauri::command]
+pub fn set_injection_policy(
+    deny_list: Vec<String>,
+    allow_list: Vec<String>,
+    blocked_action: BlockedAction,
+    queue: State<'_, InjectionQueue>,
+) -> Result<(), AppError> {
+    println!(
+        "[injection] policy set: deny {deny_list:?}, allow {allow_list:?}, {blocked_action:?}"
+    );
+    queue.set_policy(InjectionPolicy::new(
+        &deny_list,
+        &allow_list,
+        blocked_action,
+    ))
+}
+
+/// Drain the text captured by the recording backend since the last call.
+#[tauri::command]
+pub fn take_recorded_injections() -> Result<Vec<String>, AppError> {
+    recording::take()
+}
@@ -0,0 +1,265 @@
+use arboard::{Clipboard, ImageData};
+use enigo::{Direction, Key, Keyboard};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use super::{get_enigo, BackendKind, InjectionBackend};
+use crate::error::AppError;
+
+/// How long the target app gets to read the clipboard after Ctrl+V before
+/// the previous contents are put back. Most apps read it synchronously while
+/// handling the key event, but Electron/Java apps can take ~100 ms.
+const RESTORE_DELAY: Duration = Duration::from_millis(150);
+
+/// How long the clipboard owner gets to hand over one format while a
+/// snapshot is taken (or the next chunk of an X11 incremental transfer).
+/// Dictation waits on the snapshot, so a hung owner must not stall it.
+#[cfg(target_os = "linux")]
+const CONVERT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// One clipboard for the whole run: on X11, arboard serves what was set
+/// only as long as its `Clipboard` lives, and dropping the last one hands
+/// the text to a clipboard manager, if there is one, or loses it.
+static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+fn with_clipboard<T>(f: impl FnOnce(&mut Clipboard) -> Result<T, AppError>) -> Result<T, AppError> {
+    let mut guard = CLIPBOARD.lock()?;
+    let clipboard = match guard.as_mut() {
+        Some(clipboard) => clipboard,
+        None => guard.insert(
+            Clipboard::new()
+                .map_err(|e| AppError::clipboard(format!("Failed to open clipboard: {e}")))?,
+        ),
+    };
+    f(clipboard)
+}
+
+#[cfg(target_os = "linux")]
+mod wayland;
+#[cfg(windows)]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+/// Everything arboard can read back from the clipboard: the fallback where
+/// the platform's own clipboard cannot be read in full (macOS).
+#[derive(Default)]
+struct PortableSnapshot {
+    text: Option<String>,
+    html: Option<String>,
+    image: Option<ImageData<'static>>,
+    files: Option<Vec<PathBuf>>,
+}
+
+impl PortableSnapshot {
+    fn capture(clipboard: &mut Clipboard) -> Self {
+        Self {
+            text: clipboard.get().text().ok(),
+            html: clipboard.get().html().ok(),
+            image: clipboard.get().image().ok(),
+            files: clipboard.get().file_list().ok().filter(|f| !f.is_empty()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_none() && self.html.is_none() && self.image.is_none() && self.files.is_none()
+    }
+
+    /// Put the snapshot back, richest format first. arboard can only set one
+    /// format per call (HTML carries its plain-text alternative along), so
+    /// a clipboard that held several unrelated formats keeps the best one.
+    fn restore(self, clipboard: &mut Clipboard) -> Result<(), arboard::Error> {
+        if self.is_empty() {
+            return clipboard.clear();
+        }
+        if let Some(files) = self.files {
+            return clipboard.set().file_list(&files);
+        }
+        if let Some(html) = self.html {
+            return clipboard.set().html(html, self.text);
+        }
+        if let Some(image) = self.image {
+            return clipboard.set_image(image);
+        }
+        match self.text {
+            Some(text) => clipboard.set_text(text),
+            None => Ok(()),
+        }
+    }
+}
+
+/// What the user had copied before a paste, in every format it was
+/// offered in, to put back afterwards.
+enum ClipboardSnapshot {
+    #[cfg(windows)]
+    Windows(windows::Formats),
+    #[cfg(target_os = "linux")]
+    Wayland(wayland::Offers),
+    #[cfg(target_os = "linux")]
+    X11(x11::Targets),
+    Portable(PortableSnapshot),
+}
+
+impl ClipboardSnapshot {
+    /// Read the clipboard the way arboard writes it: through data-control on
+    /// Wayland when the compositor has it, through X11 otherwise.
+    fn capture(clipboard: &mut Clipboard) -> Self {
+        #[cfg(windows)]
+        if let Some(formats) = windows::capture() {
+            return Self::Windows(formats);
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(offers) = wayland::capture() {
+                return Self::Wayland(offers);
+            }
+            if let Some(targets) = x11::capture() {
+                return Self::X11(targets);
+            }
+        }
+        Self::Portable(PortableSnapshot::capture(clipboard))
+    }
+
+    fn restore(self, clipboard: &mut Clipboard) -> Result<(), String> {
+        match self {
+            #[cfg(windows)]
+            Self::Windows(formats) => windows::restore(formats),
+            #[cfg(target_os = "linux")]
+            Self::Wayland(offers) => wayland::restore(offers),
+            #[cfg(target_os = "linux")]
+            Self::X11(targets) => x11::restore(targets),
+            Self::Portable(snapshot) => snapshot.restore(clipboard).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Taken right after our text went on the clipboard, to tell later
+    /// whether it is still ours.
+    fn mark(&self, text: &str) -> ChangeMark {
+        match self {
+            #[cfg(windows)]
+            Self::Windows(_) => ChangeMark::Sequence(windows::sequence_number()),
+            #[cfg(target_os = "linux")]
+            Self::X11(_) => match x11::owner() {
+                Some(owner) => ChangeMark::Owner(owner),
+                None => ChangeMark::Text(text.to_string()),
+            },
+            _ => ChangeMark::Text(text.to_string()),
+        }
+    }
+}
+
+/// What the clipboard looked like with our text on it. The sequence number
+/// (Windows) and the selection owner (X11) change when the user copies, even
+/// the same text; elsewhere only a different text gives it away.
+enum ChangeMark {
+    #[cfg(windows)]
+    Sequence(u32),
+    #[cfg(target_os = "linux")]
+    Owner(u32),
+    Text(String),
+}
+
+impl ChangeMark {
+    fn unchanged(&self, clipboard: &mut Clipboard) -> bool {
+        match self {
+            #[cfg(windows)]
+            Self::Sequence(sequence) => windows::sequence_number() == *sequence,
+            #[cfg(target_os = "linux")]
+            Self::Owner(owner) => x11::owner() == Some(*owner),
+            Self::Text(text) => clipboard.get_text().is_ok_and(|current| current == *text),
+        }
+    }
+}
+
+/// Clipboard + simulated Ctrl+V. Fast and layout-independent, but some apps
+/// ignore synthetic paste.
+pub struct ClipboardPasteBackend;
+
+impl InjectionBackend for ClipboardPasteBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Clipboard
+    }
+
+    fn inject(&mut self, text: &str) -> Result<(), AppError> {
+        paste_preserving_clipboard(text, paste_shortcut)
+    }
+}
+
+/// Put `text` on the clipboard, run `paste` to make the focused app paste
+/// it, then put back whatever the user had copied before.
+pub fn paste_preserving_clipboard(
+    text: &str,
+    paste: impl FnOnce() -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    with_clipboard(|clipboard| paste_with(clipboard, text, paste))
+}
+
+fn paste_with(
+    clipboard: &mut Clipboard,
+    text: &str,
+    paste: impl FnOnce() -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    // Remember what the user had copied before we overwrite it
+    let snapshot = ClipboardSnapshot::capture(clipboard);
+
+    // Set clipboard content
+    clipboard
+        .set_text(text)
+        .map_err(|e| AppError::clipboard(format!("Failed to set clipboard: {e}")))?;
+    let mark = snapshot.mark(text);
+
+    // Small delay to let clipboard update propagate
+    thread::sleep(Duration::from_millis(5));
+
+    let paste_result = paste();
+
+    // Let the target app consume the paste before restoring
+    thread::sleep(RESTORE_DELAY);
+
+    // Only restore if the clipboard still holds our text: if the user copied
+    // something in the meantime, that is what they expect to keep.
+    if mark.unchanged(clipboard) {
+        if let Err(e) = snapshot.restore(clipboard) {
+            eprintln!("[injection] failed to restore clipboard: {e}");
+        }
+    } else {
+        println!("[injection] clipboard changed during paste, not restoring");
+    }
+
+    paste_result
+}
+
+/// Leave `text` on the clipboard for the user to paste themselves. Only
+/// succeeds once the clipboard reads back as `text`.
+pub fn copy_to_clipboard(text: &str) -> Result<(), AppError> {
+    with_clipboard(|clipboard| {
+        clipboard
+            .set_text(text)
+            .map_err(|e| AppError::clipboard(format!("Failed to set clipboard: {e}")))?;
+        match clipboard.get_text() {
+            Ok(current) if current == text => Ok(()),
+            Ok(_) => Err(AppError::clipboard("The clipboard did not keep the text")),
+            Err(e) => Err(AppError::clipboard(format!(
+                "Failed to read the clipboard back: {e}"
+            ))),
+        }
+    })
+}
+
+/// Simulate Ctrl+V through enigo.
+fn paste_shortcut() -> Result<(), AppError> {
+    let mut guard = get_enigo()?;
+    let enigo = guard.as_mut().unwrap();
+    enigo
+        .key(Key::Control, Direction::Press)
+        .map_err(|e| AppError::input(format!("Ctrl press failed: {e}")))?;
+    enigo
+        .key(Key::Unicode('v'), Direction::Click)
+        .map_err(|e| AppError::input(format!("V click failed: {e}")))?;
+    enigo
+        .key(Key::Control, Direction::Release)
+        .map_err(|e| AppError::input(format!("Ctrl release failed: {e}")))?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! The Wayland clipboard in every MIME type it is offered in, read before a
+//! paste and offered again after it. Needs the data-control protocol, as
+//! arboard does; without it arboard falls back to XWayland.
+
+use std::io::{ErrorKind, Read};
+use std::os::fd::AsRawFd;
+use std::time::Instant;
+use wl_clipboard_rs::copy::{self, MimeSource, Source};
+use wl_clipboard_rs::paste::{self, ClipboardType, Error, MimeType, Seat};
+
+use super::CONVERT_TIMEOUT;
+
+/// Largest MIME type kept; a bigger one (a copied video, say) is left out
+/// of the snapshot.
+const MAX_OFFER_BYTES: usize = 16 * 1024 * 1024;
+
+/// Each MIME type with its contents.
+pub struct Offers(Vec<(String, Vec<u8>)>);
+
+/// Everything the source writes into `pipe`, unless it stays silent for
+/// `CONVERT_TIMEOUT` (as the X11 path waits per chunk) or sends more than
+/// `MAX_OFFER_BYTES`.
+fn read_offer(mut pipe: impl Read + AsRawFd) -> Option<Vec<u8>> {
+    let fd = pipe.as_raw_fd();
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0 {
+        return None;
+    }
+    let mut deadline = Instant::now() + CONVERT_TIMEOUT;
+    let mut data = Vec::new();
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+        match pipe.read(&mut chunk) {
+            Ok(0) => return Some(data),
+            Ok(n) => {
+                data.extend_from_slice(&chunk[..n]);
+                deadline = Instant::now() + CONVERT_TIMEOUT;
+                if data.len() > MAX_OFFER_BYTES {
+                    return None;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                let left = deadline.checked_duration_since(Instant::now())?;
+                let mut pollfd = libc::pollfd {
+                    fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let timeout = left.as_millis().max(1) as libc::c_int;
+                unsafe { libc::poll(&mut pollfd, 1, timeout) };
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(_) => return None,
+        }
+    }
+}
+
+/// `None` without a compositor that supports data-control.
+pub fn capture() -> Option<Offers> {
+    std::env::var_os("WAYLAND_DISPLAY")?;
+    let types = match paste::get_mime_types_ordered(ClipboardType::Regular, Seat::Unspecified) {
+        Ok(types) => types,
+        Err(Error::NoSeats | Error::ClipboardEmpty | Error::NoMimeType) => {
+            return Some(Offers(Vec::new()))
+        }
+        Err(_) => return None,
+    };
+    let offers = types
+        .into_iter()
+        .filter_map(|mime_type| {
+            let (pipe, _) = paste::get_contents(
+                ClipboardType::Regular,
+                Seat::Unspecified,
+                MimeType::Specific(&mime_type),
+            )
+            .ok()?;
+            let data = read_offer(pipe)?;
+            Some((mime_type, data))
+        })
+        .collect();
+    Some(Offers(offers))
+}
+
+/// Offer `offers` again, served from a thread of wl-clipboard-rs's own.
+pub fn restore(offers: Offers) -> Result<(), String> {
+    if offers.0.is_empty() {
+        return copy::clear(copy::ClipboardType::Regular, copy::Seat::All)
+            .map_err(|e| e.to_string());
+    }
+    let sources = offers
+        .0
+        .into_iter()
+        .map(|(mime_type, data)| MimeSource {
+            source: Source::Bytes(data.into_boxed_slice()),
+            mime_type: copy::MimeType::Specific(mime_type),
+        })
+        .collect();
+    let mut options = copy::Options::new();
+    // Offer exactly what was there
+    options.omit_additional_text_mime_types(true);
+    options.copy_multi(sources).map_err(|e| e.to_string())
+}
@@ -0,0 +1,138 @@
+//! The Windows clipboard in every format it holds, copied out before a
+//! paste and put back after it.
+
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+use windows_sys::Win32::Foundation::GlobalFree;
+use windows_sys::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData,
+    GetClipboardSequenceNumber, OpenClipboard, SetClipboardData,
+};
+use windows_sys::Win32::System::Memory::{
+    GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+};
+use windows_sys::Win32::System::Ole::{
+    CF_BITMAP, CF_DSPBITMAP, CF_DSPENHMETAFILE, CF_DSPMETAFILEPICT, CF_ENHMETAFILE, CF_GDIOBJLAST,
+    CF_METAFILEPICT, CF_OWNERDISPLAY, CF_PALETTE, CF_PRIVATEFIRST,
+};
+
+/// Another app may have the clipboard open for a moment.
+const OPEN_ATTEMPTS: u32 = 10;
+const OPEN_RETRY: Duration = Duration::from_millis(10);
+
+/// Each format with the bytes of its memory block.
+pub struct Formats(Vec<(u32, Vec<u8>)>);
+
+/// The clipboard, open until dropped.
+struct Open;
+
+impl Open {
+    fn new() -> Option<Self> {
+        for _ in 0..OPEN_ATTEMPTS {
+            if unsafe { OpenClipboard(ptr::null_mut()) } != 0 {
+                return Some(Open);
+            }
+            thread::sleep(OPEN_RETRY);
+        }
+        None
+    }
+}
+
+impl Drop for Open {
+    fn drop(&mut self) {
+        unsafe { CloseClipboard() };
+    }
+}
+
+/// Whether the format is a memory block that can be copied. GDI handles
+/// (bitmaps, metafiles, palettes) are not, but Windows synthesizes them
+/// again from CF_DIB and the like; private formats are the owner's own.
+fn is_memory(format: u32) -> bool {
+    let Ok(format) = u16::try_from(format) else {
+        return false;
+    };
+    !matches!(
+        format,
+        CF_BITMAP
+            | CF_METAFILEPICT
+            | CF_PALETTE
+            | CF_ENHMETAFILE
+            | CF_OWNERDISPLAY
+            | CF_DSPBITMAP
+            | CF_DSPMETAFILEPICT
+            | CF_DSPENHMETAFILE
+    ) && !(CF_PRIVATEFIRST..=CF_GDIOBJLAST).contains(&format)
+}
+
+pub fn capture() -> Option<Formats> {
+    let _open = Open::new()?;
+    let mut formats = Vec::new();
+    let mut format = 0;
+    loop {
+        format = unsafe { EnumClipboardFormats(format) };
+        if format == 0 {
+            break;
+        }
+        if !is_memory(format) {
+            continue;
+        }
+        let handle = unsafe { GetClipboardData(format) };
+        if handle.is_null() {
+            continue;
+        }
+        unsafe {
+            let size = GlobalSize(handle);
+            let data = GlobalLock(handle);
+            if data.is_null() {
+                continue;
+            }
+            formats.push((
+                format,
+                std::slice::from_raw_parts(data as *const u8, size).to_vec(),
+            ));
+            GlobalUnlock(handle);
+        }
+    }
+    Some(Formats(formats))
+}
+
+pub fn restore(formats: Formats) -> Result<(), String> {
+    let _open = Open::new().ok_or("clipboard is in use")?;
+    if unsafe { EmptyClipboard() } == 0 {
+        return Err("failed to empty the clipboard".into());
+    }
+    // Restore what we can, but report anything lost
+    let mut failed = Vec::new();
+    for (format, bytes) in formats.0 {
+        unsafe {
+            let block = GlobalAlloc(GMEM_MOVEABLE, bytes.len().max(1));
+            if block.is_null() {
+                return Err("out of memory".into());
+            }
+            let data = GlobalLock(block);
+            if data.is_null() {
+                GlobalFree(block);
+                failed.push(format);
+                continue;
+            }
+            ptr::copy_nonoverlapping(bytes.as_ptr(), data as *mut u8, bytes.len());
+            GlobalUnlock(block);
+            // The clipboard owns the block once it is set
+            if SetClipboardData(format, block).is_null() {
+                GlobalFree(block);
+                failed.push(format);
+            }
+        }
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("failed to restore clipboard format(s) {failed:?}"))
+    }
+}
+
+/// Bumped on every change of the clipboard contents.
+pub fn sequence_number() -> u32 {
+    unsafe { GetClipboardSequenceNumber() }
+}
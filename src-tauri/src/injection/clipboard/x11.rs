@@ -0,0 +1,415 @@
+//! The X11 CLIPBOARD selection in every target its owner offers, read
+//! before a paste and served again after it, until someone else copies.
+
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, CreateWindowAux, EventMask,
+    PropMode, Property, SelectionNotifyEvent, SelectionRequestEvent, Timestamp, Window,
+    WindowClass, SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME, NONE};
+
+use super::CONVERT_TIMEOUT;
+
+/// Largest chunk of an incremental (INCR) transfer we serve.
+const MAX_CHUNK: usize = 256 * 1024;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        CLIPBOARD,
+        TARGETS,
+        MULTIPLE,
+        TIMESTAMP,
+        SAVE_TARGETS,
+        DELETE,
+        INCR,
+        OPENWHISPER_SELECTION,
+    }
+}
+
+/// One target of the selection, with the type and format it came in.
+struct Target {
+    name: Atom,
+    kind: Atom,
+    format: u8,
+    data: Vec<u8>,
+}
+
+pub struct Targets(Vec<Target>);
+
+/// An incremental transfer to a requestor, sent chunk by chunk as it
+/// deletes the property.
+struct Transfer {
+    requestor: Window,
+    property: Atom,
+    target: usize,
+    offset: usize,
+}
+
+/// A connection with a window of our own to receive or own the selection.
+struct Session {
+    conn: RustConnection,
+    window: Window,
+    atoms: Atoms,
+}
+
+impl Session {
+    fn open() -> Option<Self> {
+        let (conn, screen) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots[screen].root;
+        let window = conn.generate_id().ok()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )
+        .ok()?;
+        let atoms = Atoms::new(&conn).ok()?.reply().ok()?;
+        Some(Self {
+            conn,
+            window,
+            atoms,
+        })
+    }
+
+    fn owner(&self) -> Option<Window> {
+        let reply = self
+            .conn
+            .get_selection_owner(self.atoms.CLIPBOARD)
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(reply.owner)
+    }
+
+    /// The next event `wanted` picks, unless none comes in time.
+    fn wait<T>(&self, wanted: impl Fn(Event) -> Option<T>) -> Option<T> {
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        while Instant::now() < deadline {
+            match self.conn.poll_for_event().ok()? {
+                Some(event) => {
+                    if let Some(found) = wanted(event) {
+                        return Some(found);
+                    }
+                }
+                None => thread::sleep(Duration::from_millis(2)),
+            }
+        }
+        None
+    }
+
+    /// The server's current time, which ICCCM wants instead of
+    /// `CurrentTime` when taking a selection: from the PropertyNotify that
+    /// an empty append to our own window generates.
+    fn server_time(&self) -> Option<Timestamp> {
+        let property = self.atoms.OPENWHISPER_SELECTION;
+        self.conn
+            .change_property8(
+                PropMode::APPEND,
+                self.window,
+                property,
+                AtomEnum::STRING,
+                &[],
+            )
+            .ok()?;
+        self.conn.flush().ok()?;
+        self.wait(|event| match event {
+            Event::PropertyNotify(e) if e.window == self.window && e.atom == property => {
+                Some(e.time)
+            }
+            _ => None,
+        })
+    }
+
+    /// Ask the owner for the selection as `target`: its type, format and
+    /// data, or `None` if it refuses.
+    fn convert(&self, target: Atom) -> Option<(Atom, u8, Vec<u8>)> {
+        let property = self.atoms.OPENWHISPER_SELECTION;
+        self.conn
+            .convert_selection(
+                self.window,
+                self.atoms.CLIPBOARD,
+                target,
+                property,
+                CURRENT_TIME,
+            )
+            .ok()?;
+        self.conn.flush().ok()?;
+        let converted = self.wait(|event| match event {
+            Event::SelectionNotify(e) if e.requestor == self.window => Some(e.property != NONE),
+            _ => None,
+        })?;
+        if !converted {
+            return None;
+        }
+
+        let reply = self
+            .conn
+            .get_property(true, self.window, property, AtomEnum::ANY, 0, u32::MAX / 4)
+            .ok()?
+            .reply()
+            .ok()?;
+        if reply.type_ != self.atoms.INCR {
+            return Some((reply.type_, reply.format, reply.value));
+        }
+
+        // Deleting the INCR property asked for the first chunk; an empty
+        // one ends the transfer
+        let (mut kind, mut format, mut data) = (NONE, 8, Vec::new());
+        loop {
+            self.wait(|event| match event {
+                Event::PropertyNotify(e)
+                    if e.window == self.window
+                        && e.atom == property
+                        && e.state == Property::NEW_VALUE =>
+                {
+                    Some(())
+                }
+                _ => None,
+            })?;
+            let chunk = self
+                .conn
+                .get_property(true, self.window, property, AtomEnum::ANY, 0, u32::MAX / 4)
+                .ok()?
+                .reply()
+                .ok()?;
+            if chunk.value.is_empty() {
+                return Some((kind, format, data));
+            }
+            (kind, format) = (chunk.type_, chunk.format);
+            data.extend(chunk.value);
+        }
+    }
+
+    /// Answer a request for one of `targets`, or for the list of them.
+    fn answer(
+        &self,
+        request: &SelectionRequestEvent,
+        targets: &[Target],
+        acquired: Timestamp,
+        transfers: &mut Vec<Transfer>,
+    ) {
+        // Obsolete clients leave the property to us
+        let property = if request.property == NONE {
+            request.target
+        } else {
+            request.property
+        };
+        let answered = if request.target == self.atoms.TARGETS {
+            let names: Vec<Atom> = [self.atoms.TARGETS, self.atoms.TIMESTAMP]
+                .into_iter()
+                .chain(targets.iter().map(|t| t.name))
+                .collect();
+            self.conn
+                .change_property32(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    AtomEnum::ATOM,
+                    &names,
+                )
+                .is_ok()
+        } else if request.target == self.atoms.TIMESTAMP {
+            self.conn
+                .change_property32(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    AtomEnum::INTEGER,
+                    &[acquired],
+                )
+                .is_ok()
+        } else if let Some(index) = targets.iter().position(|t| t.name == request.target) {
+            let target = &targets[index];
+            if target.data.len() > self.chunk_size() {
+                // Too big for one request: hand it over in chunks
+                let started = self
+                    .conn
+                    .change_window_attributes(
+                        request.requestor,
+                        &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+                    )
+                    .and_then(|_| {
+                        self.conn.change_property32(
+                            PropMode::REPLACE,
+                            request.requestor,
+                            property,
+                            self.atoms.INCR,
+                            &[target.data.len() as u32],
+                        )
+                    })
+                    .is_ok();
+                if started {
+                    transfers.push(Transfer {
+                        requestor: request.requestor,
+                        property,
+                        target: index,
+                        offset: 0,
+                    });
+                }
+                started
+            } else {
+                self.put(request.requestor, property, target, &target.data)
+            }
+        } else {
+            false
+        };
+
+        let notify = SelectionNotifyEvent {
+            response_type: SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: request.time,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: if answered { property } else { NONE },
+        };
+        let _ = self
+            .conn
+            .send_event(false, request.requestor, EventMask::NO_EVENT, notify);
+        let _ = self.conn.flush();
+    }
+
+    fn chunk_size(&self) -> usize {
+        // Leave room for the request header, in whole 32-bit units
+        (self.conn.maximum_request_bytes().saturating_sub(64) / 4 * 4).min(MAX_CHUNK)
+    }
+
+    fn put(&self, requestor: Window, property: Atom, target: &Target, data: &[u8]) -> bool {
+        let unit = usize::from(target.format / 8).max(1);
+        self.conn
+            .change_property(
+                PropMode::REPLACE,
+                requestor,
+                property,
+                target.kind,
+                target.format,
+                (data.len() / unit) as u32,
+                data,
+            )
+            .is_ok()
+    }
+
+    /// Send the next chunk to a requestor that took the previous one; an
+    /// empty chunk ends the transfer.
+    fn next_chunk(
+        &self,
+        window: Window,
+        property: Atom,
+        targets: &[Target],
+        transfers: &mut Vec<Transfer>,
+    ) {
+        let Some(i) = transfers
+            .iter()
+            .position(|t| t.requestor == window && t.property == property)
+        else {
+            return;
+        };
+        let transfer = &mut transfers[i];
+        let target = &targets[transfer.target];
+        let end = (transfer.offset + self.chunk_size()).min(target.data.len());
+        let chunk = &target.data[transfer.offset..end];
+        self.put(window, property, target, chunk);
+        let _ = self.conn.flush();
+        if chunk.is_empty() {
+            transfers.remove(i);
+        } else {
+            transfer.offset = end;
+        }
+    }
+
+    /// Serve `targets`, owned since `acquired`, until another client takes
+    /// the selection.
+    fn serve(self, targets: Vec<Target>, acquired: Timestamp) {
+        let mut transfers = Vec::new();
+        while let Ok(event) = self.conn.wait_for_event() {
+            match event {
+                Event::SelectionClear(e) if e.selection == self.atoms.CLIPBOARD => return,
+                Event::SelectionRequest(request) => {
+                    self.answer(&request, &targets, acquired, &mut transfers)
+                }
+                Event::PropertyNotify(e) if e.state == Property::DELETE => {
+                    self.next_chunk(e.window, e.atom, &targets, &mut transfers)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Everything the current owner offers; `None` if there is no X server or
+/// the owner cannot list its targets.
+pub fn capture() -> Option<Targets> {
+    let session = Session::open()?;
+    if session.owner()? == NONE {
+        return Some(Targets(Vec::new()));
+    }
+    let (_, _, list) = session.convert(session.atoms.TARGETS)?;
+    let atoms = &session.atoms;
+    // Targets that are requests rather than data
+    let skipped = [
+        atoms.TARGETS,
+        atoms.MULTIPLE,
+        atoms.TIMESTAMP,
+        atoms.SAVE_TARGETS,
+        atoms.DELETE,
+    ];
+    let targets = list
+        .chunks_exact(4)
+        .map(|bytes| Atom::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .filter(|name| !skipped.contains(name))
+        .filter_map(|name| {
+            let (kind, format, data) = session.convert(name)?;
+            Some(Target {
+                name,
+                kind,
+                format,
+                data,
+            })
+        })
+        .collect();
+    Some(Targets(targets))
+}
+
+/// Take the selection back with `targets`, served from a thread of its own.
+pub fn restore(targets: Targets) -> Result<(), String> {
+    let session = Session::open().ok_or("no X server")?;
+    let time = session
+        .server_time()
+        .ok_or("no timestamp from the X server")?;
+    let owner = if targets.0.is_empty() {
+        NONE
+    } else {
+        session.window
+    };
+    session
+        .conn
+        .set_selection_owner(owner, session.atoms.CLIPBOARD, time)
+        .map_err(|e| e.to_string())?;
+    if owner == NONE {
+        return session.conn.flush().map_err(|e| e.to_string());
+    }
+    if session.owner() != Some(session.window) {
+        return Err("could not take the clipboard".into());
+    }
+    thread::spawn(move || session.serve(targets.0, time));
+    Ok(())
+}
+
+/// The window owning the clipboard: a different one means someone copied.
+pub fn owner() -> Option<Window> {
+    Session::open()?.owner()
+}
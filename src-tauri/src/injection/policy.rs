@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use crate::focus::AppIdentity;
+
+/// Applications dictation never types into unless the user edits
+/// `injection.deny_list`: password managers and credential prompts, plus
+/// `sudo`/`su` so a terminal asking for a password is covered too. The
+/// list lives with the backend, whose config defaults read the same file.
+pub fn default_deny_list() -> Vec<String> {
+    serde_json::from_str(include_str!("../../../backend/src/default_deny_list.json"))
+        .expect("default_deny_list.json is a list of strings")
+}
+
+/// What happens to text whose target is blocked
+/// (`injection.blocked_action` in `config.yaml`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockedAction {
+    /// Put the session's text on the clipboard for the user to paste.
+    #[default]
+    Clipboard,
+    /// Discard it.
+    Drop,
+}
+
+/// Why a window was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReason {
+    /// The app matches an entry of the deny-list.
+    Denied,
+    /// An allow-list is configured and the app is not on it.
+    NotAllowed,
+    /// An allow-list is configured and the focused app cannot be identified
+    /// (e.g. on Wayland).
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct Blocked {
+    pub reason: BlockReason,
+    /// The name that matched, or the best name for the app.
+    pub app: Option<String>,
+}
+
+/// Deny-list and allow-list checked against the focused window before
+/// every injection. Entries are matched case-insensitively against the
+/// window class, the process name (without `.exe`) and, on Linux, the
+/// processes running under it in the foreground of their terminal; a
+/// trailing `*` matches a prefix.
+#[derive(Debug, Clone)]
+pub struct InjectionPolicy {
+    deny_list: Vec<String>,
+    allow_list: Vec<String>,
+    pub blocked_action: BlockedAction,
+}
+
+impl InjectionPolicy {
+    pub fn new(deny_list: &[String], allow_list: &[String], blocked_action: BlockedAction) -> Self {
+        Self {
+            deny_list: deny_list.iter().map(|e| normalize(e)).collect(),
+            allow_list: allow_list.iter().map(|e| normalize(e)).collect(),
+            blocked_action,
+        }
+    }
+
+    /// Whether any list is configured; if not, every app is allowed.
+    pub fn is_active(&self) -> bool {
+        !self.deny_list.is_empty() || !self.allow_list.is_empty()
+    }
+
+    /// `None` if text may be typed into the app described by `identity`.
+    pub fn check(&self, identity: &AppIdentity) -> Option<Blocked> {
+        if !self.is_active() {
+            return None;
+        }
+        let names: Vec<String> = identity.names().map(normalize).collect();
+
+        if let Some(name) = find_match(&self.deny_list, &names) {
+            return Some(Blocked {
+                reason: BlockReason::Denied,
+                app: Some(name.to_string()),
+            });
+        }
+        if self.allow_list.is_empty() || find_match(&self.allow_list, &names).is_some() {
+            return None;
+        }
+        let reason = if names.is_empty() {
+            BlockReason::Unknown
+        } else {
+            BlockReason::NotAllowed
+        };
+        Some(Blocked {
+            reason,
+            app: display_name(identity),
+        })
+    }
+}
+
+fn normalize(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => name,
+    }
+}
+
+/// First of `names` matched by an entry of `list`.
+fn find_match<'a>(list: &[String], names: &'a [String]) -> Option<&'a str> {
+    names
+        .iter()
+        .find(|name| {
+            list.iter().any(|entry| match entry.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => *name == entry,
+            })
+        })
+        .map(String::as_str)
+}
+
+fn display_name(identity: &AppIdentity) -> Option<String> {
+    identity
+        .process
+        .clone()
+        .or_else(|| identity.classes.last().cloned())
+}
@@ -0,0 +1,596 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::clipboard::copy_to_clipboard;
+use super::policy::{BlockReason, Blocked, BlockedAction, InjectionPolicy};
+use super::spans::{grapheme_len, SessionSpans, Target};
+use super::FocusLock;
+use crate::config::InjectionConfig;
+use crate::error::AppError;
+use crate::focus::{self, AppIdentity, WindowId};
+
+/// How often a paused queue checks whether the locked window is back.
+const FOCUS_POLL: Duration = Duration::from_millis(250);
+/// Time the window manager gets to honour a refocus request.
+const REFOCUS_DELAY: Duration = Duration::from_millis(100);
+
+/// What a job does to the text already typed into the target app.
+pub enum Edit {
+    /// Type `text` after what is already there.
+    Append(String),
+    /// Erase the last `erase` graphemes, then type `text`.
+    Replace { erase: usize, text: String },
+    /// Replace the session's provisional deltas with the final segment text.
+    Finalize(String),
+}
+
+/// One edit waiting to be injected.
+struct TextJob {
+    id: u64,
+    /// Cancellation generation the job was queued in; jobs from an older
+    /// generation are skipped instead of injected.
+    generation: u64,
+    edit: Edit,
+    /// Notified with the result once the job has run (used by `inject_text`).
+    done: Option<Sender<Result<(), AppError>>>,
+}
+
+impl TextJob {
+    fn appended_text(&self) -> Option<&str> {
+        match &self.edit {
+            Edit::Append(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The text this edit would type.
+    fn text(&self) -> &str {
+        match &self.edit {
+            Edit::Append(text) | Edit::Replace { text, .. } | Edit::Finalize(text) => text,
+        }
+    }
+}
+
+enum Job {
+    Text(TextJob),
+    /// Acknowledged once every job queued before it has completed.
+    Flush(Sender<()>),
+    /// Start tracking spans for a new dictation session, optionally locked
+    /// to the window that had focus when it was started.
+    BeginSession(i64, Option<WindowId>),
+    /// Erase everything the last dictation session typed.
+    UndoSession,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum JobStatus {
+    Done,
+    Failed,
+    Cancelled,
+    /// Refused by the deny/allow-list; see `injection:blocked`.
+    Blocked,
+}
+
+/// Payload of `injection:progress`, emitted when a job starts.
+#[derive(Clone, Serialize)]
+struct JobProgress {
+    id: u64,
+    /// Jobs still queued behind this one.
+    pending: usize,
+}
+
+/// Payload of `injection:completed`, emitted once per job.
+#[derive(Clone, Serialize)]
+struct JobCompleted {
+    id: u64,
+    status: JobStatus,
+    error: Option<AppError>,
+}
+
+/// Payload of `dictation:undone`.
+#[derive(Clone, Serialize)]
+struct Undone {
+    session_id: Option<i64>,
+    erased: usize,
+}
+
+/// Payload of `dictation:undo-refused`.
+#[derive(Clone, Serialize)]
+struct UndoRefused {
+    session_id: Option<i64>,
+    reason: &'static str,
+}
+
+/// Payload of `injection:blocked`.
+#[derive(Clone, Serialize)]
+struct InjectionBlocked {
+    reason: BlockReason,
+    app: Option<String>,
+    action: BlockedAction,
+    /// Whether the text made it onto the clipboard.
+    copied: bool,
+}
+
+/// Payload of `injection:paused` and `injection:resumed`.
+#[derive(Clone, Serialize)]
+struct TargetFocus {
+    window: WindowId,
+}
+
+/// FIFO of injection jobs drained by a dedicated worker thread, so that
+/// Tauri commands return immediately and deltas are injected in the order
+/// they were queued, however slowly the target app consumes them.
+pub struct InjectionQueue {
+    tx: Sender<Job>,
+    next_id: AtomicU64,
+    generation: Arc<AtomicU64>,
+    pending: Arc<AtomicUsize>,
+    focus_lock: Arc<Mutex<FocusLock>>,
+    policy: Arc<Mutex<InjectionPolicy>>,
+    /// Window captured by the dictation shortcut, claimed by the next session.
+    armed_target: Mutex<Option<WindowId>>,
+    session_active: AtomicBool,
+}
+
+impl InjectionQueue {
+    pub fn start(app: AppHandle, config: &InjectionConfig) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let generation = Arc::new(AtomicU64::new(0));
+        let pending = Arc::new(AtomicUsize::new(0));
+        let focus_lock = Arc::new(Mutex::new(config.focus_lock));
+        let policy = Arc::new(Mutex::new(InjectionPolicy::new(
+            &config.deny_list,
+            &config.allow_list,
+            config.blocked_action,
+        )));
+
+        let worker = Worker {
+            app,
+            rx,
+            backlog: VecDeque::new(),
+            spans: SessionSpans::default(),
+            held: SessionSpans::default(),
+            identities: HashMap::new(),
+            generation: generation.clone(),
+            pending: pending.clone(),
+            focus_lock: focus_lock.clone(),
+            policy: policy.clone(),
+        };
+        thread::Builder::new()
+            .name("injection".into())
+            .spawn(move || worker.run())
+            .expect("failed to spawn injection worker");
+
+        Self {
+            tx,
+            next_id: AtomicU64::new(1),
+            generation,
+            pending,
+            focus_lock,
+            policy,
+            armed_target: Mutex::new(None),
+            session_active: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_focus_lock(&self, mode: FocusLock) -> Result<(), AppError> {
+        *self.focus_lock.lock()? = mode;
+        Ok(())
+    }
+
+    pub fn set_policy(&self, policy: InjectionPolicy) -> Result<(), AppError> {
+        *self.policy.lock()? = policy;
+        Ok(())
+    }
+
+    /// Remember the focused window as the target of the dictation session
+    /// about to start. Ignored while a session is running, so the shortcut
+    /// press that stops dictation cannot move the lock.
+    pub fn arm_target(&self) {
+        if self.session_active.load(Ordering::SeqCst) {
+            return;
+        }
+        let window = focus::focused_window();
+        println!("[injection] dictation target armed: {window:?}");
+        if let Ok(mut armed) = self.armed_target.lock() {
+            *armed = window;
+        }
+    }
+
+    /// Queue an edit and return its job id.
+    pub fn enqueue(
+        &self,
+        edit: Edit,
+        done: Option<Sender<Result<(), AppError>>>,
+    ) -> Result<u64, AppError> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        let job = TextJob {
+            id,
+            generation: self.generation.load(Ordering::SeqCst),
+            edit,
+            done,
+        };
+        self.tx
+            .send(Job::Text(job))
+            .map_err(|_| AppError::queue_stopped())?;
+        Ok(id)
+    }
+
+    /// Track spans for a new dictation session; edits queued after this
+    /// apply to it.
+    pub fn begin_session(&self, session_id: i64) -> Result<(), AppError> {
+        self.session_active.store(true, Ordering::SeqCst);
+        let locked = self.armed_target.lock().ok().and_then(|mut a| a.take());
+        self.tx
+            .send(Job::BeginSession(session_id, locked))
+            .map_err(|_| AppError::queue_stopped())
+    }
+
+    /// The dictation session is over; the next shortcut press may arm a new
+    /// target. Spans stay around for undo.
+    pub fn end_session(&self) {
+        self.session_active.store(false, Ordering::SeqCst);
+        if let Ok(mut armed) = self.armed_target.lock() {
+            *armed = None;
+        }
+    }
+
+    /// Erase the last dictation session's text once pending jobs have run,
+    /// provided focus is still on the window it was typed into.
+    pub fn undo_session(&self) -> Result<(), AppError> {
+        self.tx
+            .send(Job::UndoSession)
+            .map_err(|_| AppError::queue_stopped())
+    }
+
+    /// Returns a receiver that fires once everything queued so far has run.
+    pub fn flush(&self) -> Result<Receiver<()>, AppError> {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        self.tx
+            .send(Job::Flush(ack_tx))
+            .map_err(|_| AppError::queue_stopped())?;
+        Ok(ack_rx)
+    }
+
+    /// Skip every job queued so far that has not started yet. Returns how
+    /// many jobs were outstanding (including one that may be running).
+    pub fn cancel(&self) -> usize {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.pending.load(Ordering::SeqCst)
+    }
+}
+
+struct Worker {
+    app: AppHandle,
+    rx: Receiver<Job>,
+    /// Jobs already pulled off the channel but not processed yet.
+    backlog: VecDeque<Job>,
+    spans: SessionSpans,
+    /// Text of the current session that was kept out of a blocked app.
+    held: SessionSpans,
+    /// Class and process of the windows checked against the policy this
+    /// session, looked up once per window. What runs under a window is
+    /// read again for every batch.
+    identities: HashMap<WindowId, AppIdentity>,
+    generation: Arc<AtomicU64>,
+    pending: Arc<AtomicUsize>,
+    focus_lock: Arc<Mutex<FocusLock>>,
+    policy: Arc<Mutex<InjectionPolicy>>,
+}
+
+impl Worker {
+    fn next(&mut self) -> Option<Job> {
+        match self.backlog.pop_front() {
+            Some(job) => Some(job),
+            None => self.rx.recv().ok(),
+        }
+    }
+
+    fn run(mut self) {
+        while let Some(job) = self.next() {
+            match job {
+                Job::Flush(ack) => {
+                    let _ = ack.send(());
+                }
+                Job::BeginSession(session_id, locked) => {
+                    println!(
+                        "[injection] tracking dictation session {session_id}, locked to {locked:?}"
+                    );
+                    self.spans.begin(session_id, locked);
+                    self.held.begin(session_id, None);
+                    self.identities.clear();
+                }
+                Job::UndoSession => self.undo_session(),
+                Job::Text(first) => {
+                    let batch = self.coalesce(first);
+                    self.inject_batch(batch);
+                }
+            }
+        }
+        println!("[injection] queue closed, worker exiting");
+    }
+
+    /// Merge appends that piled up behind `first` (same generation, nothing
+    /// else in between) into a single injection.
+    fn coalesce(&mut self, first: TextJob) -> Vec<TextJob> {
+        if first.appended_text().is_none() {
+            return vec![first];
+        }
+        self.backlog.extend(self.rx.try_iter());
+        let generation = first.generation;
+        let mut batch = vec![first];
+        while matches!(
+            self.backlog.front(),
+            Some(Job::Text(job)) if job.generation == generation && job.appended_text().is_some()
+        ) {
+            if let Some(Job::Text(job)) = self.backlog.pop_front() {
+                batch.push(job);
+            }
+        }
+        batch
+    }
+
+    fn inject_batch(&mut self, batch: Vec<TextJob>) {
+        let generation = batch[0].generation;
+        let ready = if generation != self.generation.load(Ordering::SeqCst) {
+            Err(AppError::cancelled())
+        } else {
+            self.wait_for_target(generation)
+        };
+
+        let result = ready.and_then(|()| {
+            let pending = self.pending.load(Ordering::SeqCst);
+            for (i, job) in batch.iter().enumerate() {
+                self.emit(
+                    "injection:progress",
+                    JobProgress {
+                        id: job.id,
+                        pending: pending.saturating_sub(i + 1),
+                    },
+                );
+            }
+            match self.check_policy() {
+                Some(refusal) => Err(self.divert(&batch, refusal)),
+                None => self.apply(&batch),
+            }
+        });
+
+        let status = match &result {
+            Ok(()) => JobStatus::Done,
+            Err(AppError::InjectionCancelled { .. } | AppError::TargetWindowChanged { .. }) => {
+                JobStatus::Cancelled
+            }
+            Err(AppError::InjectionBlocked { .. }) => JobStatus::Blocked,
+            Err(_) => JobStatus::Failed,
+        };
+        if let Err(e) = &result {
+            eprintln!("[injection] {} job(s) not injected: {e}", batch.len());
+        }
+
+        for job in batch {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            self.emit(
+                "injection:completed",
+                JobCompleted {
+                    id: job.id,
+                    status: status.clone(),
+                    error: result.as_ref().err().cloned(),
+                },
+            );
+            if let Some(done) = job.done {
+                let _ = done.send(result.clone());
+            }
+        }
+    }
+
+    /// Make sure the window the session is locked to has focus before
+    /// typing, refocusing it or pausing the queue as configured. Fails if
+    /// the jobs were cancelled while paused.
+    fn wait_for_target(&self, generation: u64) -> Result<(), AppError> {
+        let Some(target) = self.spans.locked_window() else {
+            return Ok(());
+        };
+        let mode = self.focus_lock.lock().map(|m| *m).unwrap_or_default();
+        if mode == FocusLock::Off || focus::focused_window() == Some(target) {
+            return Ok(());
+        }
+
+        if mode == FocusLock::Refocus {
+            match focus::focus_window(target) {
+                Ok(()) => {
+                    thread::sleep(REFOCUS_DELAY);
+                    if focus::focused_window() == Some(target) {
+                        println!("[injection] refocused {target:?}");
+                        return Ok(());
+                    }
+                    eprintln!("[injection] refocus of {target:?} was not honoured, pausing");
+                }
+                Err(e) => eprintln!("[injection] refocus failed ({e}), pausing"),
+            }
+        }
+
+        // Hold this job (and everything behind it) until the user is back
+        println!("[injection] focus left {target:?}, pausing injection");
+        self.emit("injection:paused", TargetFocus { window: target });
+        loop {
+            thread::sleep(FOCUS_POLL);
+            if generation != self.generation.load(Ordering::SeqCst) {
+                return Err(AppError::TargetWindowChanged {
+                    message: "Cancelled while waiting for the dictation window to regain focus"
+                        .into(),
+                });
+            }
+            if focus::focused_window() == Some(target) {
+                println!("[injection] focus back on {target:?}, resuming");
+                self.emit("injection:resumed", TargetFocus { window: target });
+                return Ok(());
+            }
+        }
+    }
+
+    fn check_policy(&mut self) -> Option<Blocked> {
+        let policy = self.policy.lock().ok()?;
+        if !policy.is_active() {
+            return None;
+        }
+        let Some(window) = focus::focused_window() else {
+            return policy.check(&AppIdentity::default());
+        };
+        let identity = self
+            .identities
+            .entry(window)
+            .or_insert_with(|| focus::app_identity(window));
+        identity.read_descendants();
+        policy.check(identity)
+    }
+
+    /// Keep a blocked batch out of the focused app: track it as held text
+    /// and, if configured, put it on the clipboard. During a session only
+    /// finalized segments are copied, so clipboard managers do not record
+    /// every delta. Returns the error reported for the jobs.
+    fn divert(&mut self, batch: &[TextJob], refusal: Blocked) -> AppError {
+        let clip = if self.held.session_id().is_some() {
+            match &batch[0].edit {
+                Edit::Append(_) => {
+                    let text: String = batch.iter().filter_map(TextJob::appended_text).collect();
+                    self.held.appended(&text);
+                    None
+                }
+                Edit::Replace { erase, text } => {
+                    self.held.erased((*erase).min(self.held.injected_len()));
+                    self.held.appended(text);
+                    None
+                }
+                Edit::Finalize(final_text) => {
+                    self.held.finalized(final_text);
+                    Some(self.held.text())
+                }
+            }
+        } else {
+            Some(batch.iter().map(TextJob::text).collect())
+        };
+
+        let action = self
+            .policy
+            .lock()
+            .map(|p| p.blocked_action)
+            .unwrap_or_default();
+        let copied = match (action, clip) {
+            (BlockedAction::Clipboard, Some(text)) => match copy_to_clipboard(&text) {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("[injection] {e}");
+                    false
+                }
+            },
+            _ => false,
+        };
+
+        let app = refusal.app.as_deref().unwrap_or("unknown app");
+        println!(
+            "[injection] blocked injection into {app} ({:?}), {action:?}",
+            refusal.reason
+        );
+        self.emit(
+            "injection:blocked",
+            InjectionBlocked {
+                reason: refusal.reason,
+                app: refusal.app.clone(),
+                action,
+                copied,
+            },
+        );
+        AppError::InjectionBlocked {
+            reason: refusal.reason,
+            message: format!("Injection into {app} blocked"),
+            app: refusal.app,
+        }
+    }
+
+    /// Carry out the batch's edit and record it in the session spans.
+    fn apply(&mut self, batch: &[TextJob]) -> Result<(), AppError> {
+        self.spans.typed_into(focus::focused_window());
+        match &batch[0].edit {
+            Edit::Append(_) => {
+                let text: String = batch.iter().filter_map(TextJob::appended_text).collect();
+                super::inject_now(&text)?;
+                self.spans.appended(&text);
+            }
+            Edit::Replace { erase, text } => {
+                // Never erase more than this session typed
+                let erase = (*erase).min(self.spans.injected_len());
+                super::replace_now(erase, text)?;
+                self.spans.erased(erase);
+                self.spans.appended(text);
+            }
+            Edit::Finalize(final_text) => {
+                let (erase, insert) = self.spans.plan_finalize(final_text);
+                println!(
+                    "[injection] finalizing session {:?}: erase {erase}, type {}",
+                    self.spans.session_id(),
+                    grapheme_len(insert)
+                );
+                super::replace_now(erase, insert)?;
+                self.spans.finalized(final_text);
+            }
+        }
+        Ok(())
+    }
+
+    fn undo_session(&mut self) {
+        let session_id = self.spans.session_id();
+        let erase = self.spans.injected_len();
+        let refusal = if erase == 0 {
+            Some("nothing_to_undo")
+        } else {
+            match self.spans.target() {
+                Target::Window(w) if focus::focused_window() == Some(w) => None,
+                Target::Window(_) => Some("window_changed"),
+                Target::None | Target::Ambiguous => Some("window_unknown"),
+            }
+        };
+        if let Some(reason) = refusal {
+            println!("[injection] undo refused: {reason}");
+            self.emit("dictation:undo-refused", UndoRefused { session_id, reason });
+            return;
+        }
+
+        match super::replace_now(erase, "") {
+            Ok(()) => {
+                self.spans.erased(erase);
+                println!("[injection] undid session {session_id:?} ({erase} graphemes)");
+                self.emit(
+                    "dictation:undone",
+                    Undone {
+                        session_id,
+                        erased: erase,
+                    },
+                );
+            }
+            Err(e) => {
+                eprintln!("[injection] undo failed: {e}");
+                self.emit(
+                    "dictation:undo-refused",
+                    UndoRefused {
+                        session_id,
+                        reason: "failed",
+                    },
+                );
+            }
+        }
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Err(e) = self.app.emit(event, payload) {
+            eprintln!("[injection] emit {event} error: {e}");
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::Mutex;
+
+use super::{BackendKind, InjectionBackend};
+use crate::error::AppError;
+
+static RECORDED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Keeps injected text in memory instead of sending it anywhere, so
+/// end-to-end tests can drive dictation and read back what would have been
+/// typed with `take_recorded_injections`.
+pub struct RecordingBackend;
+
+impl InjectionBackend for RecordingBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Recording
+    }
+
+    fn inject(&mut self, text: &str) -> Result<(), AppError> {
+        RECORDED.lock()?.push(text.to_string());
+        Ok(())
+    }
+
+    /// Recorded as a run of U+0008 (backspace) characters.
+    fn erase(&mut self, count: usize) -> Result<(), AppError> {
+        self.inject(&"\u{8}".repeat(count))
+    }
+}
+
+pub fn take() -> Result<Vec<String>, AppError> {
+    let mut recorded = RECORDED.lock()?;
+    Ok(std::mem::take(&mut *recorded))
+}
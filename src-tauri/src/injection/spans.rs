@@ -0,0 +1,237 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::focus::WindowId;
+
+/// Number of user-perceived characters in `text`, i.e. how many Backspace
+/// presses it takes to delete it. "é" written as e + U+0301 or a flag emoji
+/// is one grapheme cluster but several chars and even more bytes.
+pub fn grapheme_len(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// Where a session's text went.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Nothing typed yet.
+    #[default]
+    None,
+    /// Everything went into this window.
+    Window(WindowId),
+    /// Focus changed mid-session or could not be determined.
+    Ambiguous,
+}
+
+/// What the current dictation session has typed into the target app, split
+/// into text confirmed by `segment_complete` and provisional deltas that may
+/// still be corrected.
+#[derive(Debug, Default)]
+pub struct SessionSpans {
+    session_id: Option<i64>,
+    committed: String,
+    provisional: String,
+    target: Target,
+    /// Window the session is locked to, captured when dictation started.
+    locked: Option<WindowId>,
+}
+
+impl SessionSpans {
+    pub fn begin(&mut self, session_id: i64, locked: Option<WindowId>) {
+        *self = Self {
+            session_id: Some(session_id),
+            locked,
+            ..Self::default()
+        };
+    }
+
+    /// Graphemes typed so far in this session; the most that may be erased.
+    pub fn injected_len(&self) -> usize {
+        grapheme_len(&self.committed) + grapheme_len(&self.provisional)
+    }
+
+    /// Everything the session holds, committed text first.
+    pub fn text(&self) -> String {
+        format!("{}{}", self.committed, self.provisional)
+    }
+
+    pub fn appended(&mut self, text: &str) {
+        self.provisional.push_str(text);
+    }
+
+    /// Forget the last `count` graphemes, provisional text first.
+    pub fn erased(&mut self, count: usize) {
+        let from_provisional = count.min(grapheme_len(&self.provisional));
+        truncate_graphemes(&mut self.provisional, from_provisional);
+        truncate_graphemes(&mut self.committed, count - from_provisional);
+    }
+
+    /// How to turn the provisional text into `final_text`: graphemes to
+    /// erase, then the text to type. Only the part after the common prefix
+    /// is rewritten.
+    pub fn plan_finalize<'a>(&self, final_text: &'a str) -> (usize, &'a str) {
+        let mut prefix_bytes = 0;
+        let mut provisional = self.provisional.graphemes(true);
+        let mut common = 0;
+        for g in final_text.graphemes(true) {
+            if provisional.next() != Some(g) {
+                break;
+            }
+            prefix_bytes += g.len();
+            common += 1;
+        }
+        let erase = grapheme_len(&self.provisional) - common;
+        (erase, &final_text[prefix_bytes..])
+    }
+
+    pub fn finalized(&mut self, final_text: &str) {
+        self.committed.push_str(final_text);
+        self.provisional.clear();
+    }
+
+    /// Record the window the next edit is typed into.
+    pub fn typed_into(&mut self, window: Option<WindowId>) {
+        self.target = match (self.target, window) {
+            (Target::None, Some(w)) => Target::Window(w),
+            (Target::Window(a), Some(b)) if a == b => Target::Window(a),
+            _ => Target::Ambiguous,
+        };
+    }
+
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    pub fn locked_window(&self) -> Option<WindowId> {
+        self.locked
+    }
+
+    pub fn session_id(&self) -> Option<i64> {
+        self.session_id
+    }
+}
+
+/// Drop the last `count` grapheme clusters of `text`.
+fn truncate_graphemes(text: &mut String, count: usize) {
+    if count == 0 {
+        return;
+    }
+    let cut = text
+        .grapheme_indices(true)
+        .rev()
+        .nth(count - 1)
+        .map_or(0, |(i, _)| i);
+    text.truncate(cut);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans_with(provisional: &str) -> SessionSpans {
+        let mut spans = SessionSpans::default();
+        spans.begin(1, None);
+        spans.appended(provisional);
+        spans
+    }
+
+    #[test]
+    fn counts_graphemes_not_chars() {
+        assert_eq!(grapheme_len(""), 0);
+        assert_eq!(grapheme_len("cafe\u{301}"), 4);
+        assert_eq!(grapheme_len("👩\u{200d}💻 ok"), 4);
+        assert_eq!(grapheme_len("🇫🇷"), 1);
+    }
+
+    #[test]
+    fn truncates_whole_graphemes() {
+        let mut text = String::from("cafe\u{301}");
+        truncate_graphemes(&mut text, 1);
+        assert_eq!(text, "caf");
+
+        let mut text = String::from("hi 👩\u{200d}💻");
+        truncate_graphemes(&mut text, 1);
+        assert_eq!(text, "hi ");
+
+        let mut text = String::from("abc");
+        truncate_graphemes(&mut text, 0);
+        assert_eq!(text, "abc");
+        truncate_graphemes(&mut text, 5);
+        assert_eq!(text, "");
+    }
+
+    #[test]
+    fn finalize_equal_text_types_nothing() {
+        assert_eq!(
+            spans_with("hello world").plan_finalize("hello world"),
+            (0, "")
+        );
+    }
+
+    #[test]
+    fn finalize_longer_text_only_appends() {
+        assert_eq!(
+            spans_with("hello").plan_finalize("hello world"),
+            (0, " world")
+        );
+    }
+
+    #[test]
+    fn finalize_shorter_text_erases_the_rest() {
+        assert_eq!(spans_with("hello world").plan_finalize("hello"), (6, ""));
+    }
+
+    #[test]
+    fn finalize_rewrites_after_the_common_prefix() {
+        assert_eq!(
+            spans_with("I red the book").plan_finalize("I read the book"),
+            (10, "ad the book")
+        );
+    }
+
+    #[test]
+    fn finalize_compares_combining_marks_as_one_grapheme() {
+        // "e" then a combining accent is one grapheme, not a prefix of "e"
+        assert_eq!(
+            spans_with("cafe").plan_finalize("cafe\u{301}"),
+            (1, "e\u{301}")
+        );
+        assert_eq!(
+            spans_with("cafe\u{301}s").plan_finalize("cafe\u{301}"),
+            (1, "")
+        );
+    }
+
+    #[test]
+    fn finalize_compares_zwj_emoji_as_one_grapheme() {
+        let coder = "👩\u{200d}💻";
+        let woman = "👩";
+        assert_eq!(
+            spans_with(&format!("hi {woman}")).plan_finalize(&format!("hi {coder}")),
+            (1, coder)
+        );
+        assert_eq!(
+            spans_with(&format!("hi {coder}!")).plan_finalize(&format!("hi {coder}")),
+            (1, "")
+        );
+    }
+
+    #[test]
+    fn finalize_empty_segment_erases_everything() {
+        assert_eq!(
+            spans_with("cafe\u{301} 👩\u{200d}💻").plan_finalize(""),
+            (6, "")
+        );
+        assert_eq!(spans_with("").plan_finalize(""), (0, ""));
+        assert_eq!(spans_with("").plan_finalize("hi"), (0, "hi"));
+    }
+
+    #[test]
+    fn finalized_text_is_committed() {
+        let mut spans = spans_with("helo");
+        spans.finalized("hello");
+        assert_eq!(spans.text(), "hello");
+        assert_eq!(spans.injected_len(), 5);
+        spans.appended(" wo");
+        spans.erased(4);
+        assert_eq!(spans.text(), "hell");
+    }
+}
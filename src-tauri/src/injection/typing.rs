@@ -0,0 +1,23 @@
+use enigo::Keyboard;
+
+use super::{get_enigo, BackendKind, InjectionBackend};
+use crate::error::AppError;
+
+/// Types the text as Unicode key events via enigo. Slower than pasting for
+/// long text, but works in apps that ignore synthetic paste and never
+/// touches the clipboard.
+pub struct UnicodeBackend;
+
+impl InjectionBackend for UnicodeBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Unicode
+    }
+
+    fn inject(&mut self, text: &str) -> Result<(), AppError> {
+        let mut guard = get_enigo()?;
+        let enigo = guard.as_mut().unwrap();
+        enigo
+            .text(text)
+            .map_err(|e| AppError::input(format!("Unicode typing failed: {e}")))
+    }
+}
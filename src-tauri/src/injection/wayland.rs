@@ -0,0 +1,179 @@
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+use super::clipboard::paste_preserving_clipboard;
+use super::{BackendKind, InjectionBackend};
+use crate::error::AppError;
+
+/// Linux evdev keycodes for `ydotool key` (KEY_BACKSPACE, KEY_LEFTCTRL, KEY_V).
+const KEY_BACKSPACE: u16 = 14;
+const KEY_LEFTCTRL: u16 = 29;
+const KEY_V: u16 = 47;
+
+/// What `wtype` says on compositors without the virtual-keyboard protocol
+/// (GNOME): it will never work there.
+const WTYPE_UNSUPPORTED: &str = "does not support the virtual keyboard protocol";
+
+/// True when running inside a Wayland session, where XTest/enigo input only
+/// reaches XWayland windows (if anything).
+pub fn is_wayland_session() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some_and(|v| !v.is_empty())
+        || std::env::var("XDG_SESSION_TYPE").is_ok_and(|v| v.eq_ignore_ascii_case("wayland"))
+}
+
+/// External helpers that can inject input on Wayland, in order of preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// `wtype`: types text through the virtual-keyboard protocol
+    /// (wlroots compositors such as Sway, Hyprland, and KDE).
+    Wtype,
+    /// Clipboard (wlr data-control) + Ctrl+V sent by `ydotool` through
+    /// uinput. Works on any compositor, including GNOME, but needs `ydotoold`.
+    YdotoolPaste,
+}
+
+impl Method {
+    fn program(self) -> &'static str {
+        match self {
+            Method::Wtype => "wtype",
+            Method::YdotoolPaste => "ydotool",
+        }
+    }
+
+    fn inject(self, text: &str) -> Result<(), AppError> {
+        match self {
+            Method::Wtype => run(Command::new("wtype").arg("--").arg(text)),
+            Method::YdotoolPaste => paste_preserving_clipboard(text, || {
+                run(Command::new("ydotool").arg("key").args([
+                    format!("{KEY_LEFTCTRL}:1"),
+                    format!("{KEY_V}:1"),
+                    format!("{KEY_V}:0"),
+                    format!("{KEY_LEFTCTRL}:0"),
+                ]))
+            }),
+        }
+    }
+
+    fn erase(self, count: usize) -> Result<(), AppError> {
+        let mut command = match self {
+            Method::Wtype => Command::new("wtype"),
+            Method::YdotoolPaste => {
+                let mut command = Command::new("ydotool");
+                command.arg("key");
+                command
+            }
+        };
+        for _ in 0..count {
+            match self {
+                Method::Wtype => command.args(["-k", "BackSpace"]),
+                Method::YdotoolPaste => {
+                    command.args([format!("{KEY_BACKSPACE}:1"), format!("{KEY_BACKSPACE}:0")])
+                }
+            };
+        }
+        run(&mut command)
+    }
+}
+
+/// Run a helper. A missing binary, or a compositor it cannot work with, is
+/// `BackendUnavailable`; any other failure may not happen again.
+fn run(command: &mut Command) -> Result<(), AppError> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(AppError::backend_unavailable(
+                BackendKind::Wayland,
+                format!("{program} not found"),
+            ))
+        }
+        Err(e) => return Err(AppError::input(format!("Failed to run {program}: {e}"))),
+    };
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = format!("{program} failed ({}): {}", output.status, stderr.trim());
+    if stderr.contains(WTYPE_UNSUPPORTED) {
+        Err(AppError::backend_unavailable(BackendKind::Wayland, message))
+    } else {
+        Err(AppError::input(message))
+    }
+}
+
+fn in_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file())
+    })
+}
+
+/// Injects through whichever Wayland helper is installed. A method that can
+/// never work (its helper is gone, or `wtype` on GNOME, which lacks the
+/// virtual-keyboard protocol) is dropped; one that just failed (`ydotoold`
+/// restarting) is kept, and the next one is tried this time.
+pub struct WaylandBackend {
+    methods: Vec<Method>,
+}
+
+impl WaylandBackend {
+    pub fn detect() -> Self {
+        let methods: Vec<Method> = [Method::Wtype, Method::YdotoolPaste]
+            .into_iter()
+            .filter(|m| in_path(m.program()))
+            .collect();
+        if methods.is_empty() {
+            eprintln!("[injection] {}", no_method_error());
+        } else {
+            println!("[injection] Wayland methods available: {methods:?}");
+        }
+        Self { methods }
+    }
+
+    /// Run `action` with the first method that works, dropping the ones
+    /// that never will.
+    fn with_method(
+        &mut self,
+        action: impl Fn(Method) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let mut failure = None;
+        let mut i = 0;
+        while let Some(&method) = self.methods.get(i) {
+            match action(method) {
+                Ok(()) => return Ok(()),
+                Err(e @ AppError::BackendUnavailable { .. }) => {
+                    eprintln!("[injection] {method:?} unusable, dropping it: {e}");
+                    self.methods.remove(i);
+                }
+                Err(e) => {
+                    eprintln!("[injection] {method:?} failed, trying the next method: {e}");
+                    failure = Some(e);
+                    i += 1;
+                }
+            }
+        }
+        Err(failure.unwrap_or_else(no_method_error))
+    }
+}
+
+fn no_method_error() -> AppError {
+    AppError::backend_unavailable(
+        BackendKind::Wayland,
+        "No Wayland text injection method available: install wtype (Sway, Hyprland, KDE) \
+         or ydotool with ydotoold running (GNOME and others)",
+    )
+}
+
+impl InjectionBackend for WaylandBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Wayland
+    }
+
+    fn inject(&mut self, text: &str) -> Result<(), AppError> {
+        self.with_method(|method| method.inject(text))
+    }
+
+    fn erase(&mut self, count: usize) -> Result<(), AppError> {
+        self.with_method(|method| method.erase(count))
+    }
+}
@@ -0,0 +1,150 @@
+use std::thread;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    ConnectionExt as _, Keycode, Keysym, Window, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+};
+use x11rb::protocol::xtest::ConnectionExt as _;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+use super::{BackendKind, InjectionBackend};
+use crate::error::AppError;
+
+/// Gives the target client time to process the MappingNotify for the
+/// scratch keycode before it is remapped to the next character.
+const REMAP_DELAY: Duration = Duration::from_millis(8);
+
+const XK_BACKSPACE: Keysym = 0xff08;
+const XK_TAB: Keysym = 0xff09;
+const XK_RETURN: Keysym = 0xff0d;
+
+/// Types text through the XTest extension, xdotool-style: characters that
+/// already have an unshifted key are sent directly, anything else is
+/// temporarily bound to an unused keycode. Independent of the clipboard and
+/// of enigo's layout handling.
+pub struct XTestBackend {
+    conn: RustConnection,
+    root: Window,
+    min_keycode: Keycode,
+    keysyms_per_keycode: u8,
+    /// First-column keysym of every keycode, indexed from `min_keycode`.
+    base_keysyms: Vec<Keysym>,
+    scratch: Keycode,
+}
+
+impl XTestBackend {
+    pub fn connect() -> Result<Self, AppError> {
+        let (conn, screen) = x11rb::connect(None)
+            .map_err(|e| AppError::no_display(format!("Failed to connect to X server: {e}")))?;
+        let unavailable = |e| {
+            AppError::backend_unavailable(BackendKind::Xtest, format!("XTest unavailable: {e}"))
+        };
+        conn.xtest_get_version(2, 2)
+            .map_err(|e| unavailable(e.to_string()))?
+            .reply()
+            .map_err(|e| unavailable(e.to_string()))?;
+
+        let setup = conn.setup();
+        let root = setup.roots[screen].root;
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+            .map_err(|e| AppError::no_display(format!("Failed to query keyboard mapping: {e}")))?
+            .reply()
+            .map_err(|e| AppError::no_display(format!("Failed to query keyboard mapping: {e}")))?;
+        let per = mapping.keysyms_per_keycode;
+        let rows: Vec<&[Keysym]> = mapping.keysyms.chunks(per as usize).collect();
+
+        // An unbound keycode we can point at arbitrary keysyms
+        let scratch = rows
+            .iter()
+            .rposition(|syms| syms.iter().all(|&s| s == 0))
+            .map(|i| min_keycode + i as u8)
+            .ok_or_else(|| {
+                AppError::backend_unavailable(
+                    BackendKind::Xtest,
+                    "No free keycode available for XTest typing",
+                )
+            })?;
+
+        Ok(Self {
+            base_keysyms: rows.iter().map(|syms| syms[0]).collect(),
+            conn,
+            root,
+            min_keycode,
+            keysyms_per_keycode: per,
+            scratch,
+        })
+    }
+
+    fn bind_scratch(&self, keysym: Keysym) -> Result<(), AppError> {
+        let syms = vec![keysym; self.keysyms_per_keycode as usize];
+        self.conn
+            .change_keyboard_mapping(1, self.scratch, self.keysyms_per_keycode, &syms)
+            .map_err(|e| AppError::input(format!("Failed to remap keycode: {e}")))?;
+        self.conn
+            .sync()
+            .map_err(|e| AppError::no_display(format!("X connection lost: {e}")))?;
+        Ok(())
+    }
+
+    fn click(&self, keycode: Keycode) -> Result<(), AppError> {
+        for event in [KEY_PRESS_EVENT, KEY_RELEASE_EVENT] {
+            self.conn
+                .xtest_fake_input(event, keycode, 0, self.root, 0, 0, 0)
+                .map_err(|e| AppError::input(format!("XTest fake input failed: {e}")))?;
+        }
+        self.conn
+            .sync()
+            .map_err(|e| AppError::no_display(format!("X connection lost: {e}")))?;
+        Ok(())
+    }
+
+    fn type_char(&self, c: char) -> Result<(), AppError> {
+        self.type_keysym(char_to_keysym(c))
+    }
+
+    fn type_keysym(&self, keysym: Keysym) -> Result<(), AppError> {
+        if let Some(i) = self.base_keysyms.iter().position(|&s| s == keysym) {
+            return self.click(self.min_keycode + i as u8);
+        }
+        self.bind_scratch(keysym)?;
+        self.click(self.scratch)?;
+        thread::sleep(REMAP_DELAY);
+        Ok(())
+    }
+}
+
+impl InjectionBackend for XTestBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Xtest
+    }
+
+    fn inject(&mut self, text: &str) -> Result<(), AppError> {
+        let result = text.chars().try_for_each(|c| self.type_char(c));
+        // Leave the scratch keycode unbound again
+        let _ = self.bind_scratch(0);
+        result
+    }
+
+    /// Sends BackSpace through XTest too, so erasing goes down the same
+    /// path as typing and stays ordered with it.
+    fn erase(&mut self, count: usize) -> Result<(), AppError> {
+        let result = (0..count).try_for_each(|_| self.type_keysym(XK_BACKSPACE));
+        let _ = self.bind_scratch(0);
+        result
+    }
+}
+
+/// Latin-1 keysyms equal their code point; everything else uses the
+/// Unicode keysym range (`0x0100_0000 + code point`).
+fn char_to_keysym(c: char) -> Keysym {
+    match c {
+        '\n' => XK_RETURN,
+        '\t' => XK_TAB,
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as Keysym,
+        _ => 0x0100_0000 | c as Keysym,
+    }
+}
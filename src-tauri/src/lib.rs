@@ -1,22 +1,85 @@
+mod backend;
+mod cli;
+mod config;
+#[cfg(target_os = "linux")]
+mod dbus;
+mod error;
+mod focus;
 mod injection;
+mod mic;
 mod shortcuts;
 mod tray;
 
+use tauri::Manager;
+
+use error::AppError;
+
 #[tauri::command]
-fn start_drag(window: tauri::Window) -> Result<(), String> {
-    window.start_dragging().map_err(|e| e.to_string())
+fn start_drag(window: tauri::Window) -> Result<(), AppError> {
+    window
+        .start_dragging()
+        .map_err(|e| AppError::WindowOperationFailed {
+            message: format!("Failed to start dragging: {e}"),
+        })
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // `openwhisper <command>` only talks to the running instance
+    if let Some(code) = cli::run_command() {
+        std::process::exit(code);
+    }
+    let config = config::load_config();
+
     tauri::Builder::default()
+        // Must come first, so a second launch exits before setting anything up
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            cli::forwarded(app, &args)
+        }))
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .invoke_handler(tauri::generate_handler![injection::inject_text, start_drag])
-        .setup(|app| {
-            shortcuts::register_shortcuts(app);
+        .invoke_handler(tauri::generate_handler![
+            injection::inject_text,
+            injection::enqueue_injection,
+            injection::flush_injection,
+            injection::cancel_injection,
+            injection::replace_injected_text,
+            injection::begin_injection_session,
+            injection::end_injection_session,
+            injection::finalize_injected_segment,
+            injection::get_injection_backend,
+            injection::set_injection_backend,
+            injection::set_focus_lock,
+            injection::set_injection_policy,
+            injection::take_recorded_injections,
+            shortcuts::apply_shortcuts,
+            shortcuts::get_shortcut_status,
+            shortcuts::capture_shortcut,
+            backend::get_backend_status,
+            backend::get_backend_connection,
+            start_drag
+        ])
+        .setup(move |app| {
+            backend::init(&config.backend);
+            backend::start(app.handle(), &config.backend);
+            injection::init(&config.injection);
+            app.manage(injection::InjectionQueue::start(
+                app.handle().clone(),
+                &config.injection,
+            ));
+            shortcuts::register_shortcuts(app, &config.shortcuts);
+            mic::init(app);
+            cli::listen(app.handle());
+            #[cfg(target_os = "linux")]
+            dbus::start(app);
             tray::create_tray(app).expect("failed to create system tray");
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                backend::stop();
+                cli::close();
+            }
+        });
 }
@@ -0,0 +1,95 @@
+//! The microphone state as the frontend last reported it (`mic-state-changed`),
+//! for the parts of the app that answer about it outside the tray.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{App, Listener};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MicPhase {
+    Idle,
+    Connecting,
+    LoadingModel,
+    Recording,
+    Finalizing,
+    Error,
+}
+
+#[cfg(unix)]
+impl MicPhase {
+    /// As serialized: "loading_model".
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MicPhase::Idle => "idle",
+            MicPhase::Connecting => "connecting",
+            MicPhase::LoadingModel => "loading_model",
+            MicPhase::Recording => "recording",
+            MicPhase::Finalizing => "finalizing",
+            MicPhase::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MicMode {
+    Dictation,
+    Transcription,
+    None,
+}
+
+#[cfg(unix)]
+impl MicMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MicMode::Dictation => "dictation",
+            MicMode::Transcription => "transcription",
+            MicMode::None => "none",
+        }
+    }
+}
+
+/// Payload of `mic-state-changed` (`MicStatePayload` in the frontend).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MicState {
+    pub state: MicPhase,
+    pub mode: MicMode,
+    pub language: String,
+}
+
+impl MicState {
+    /// A session is running: the mic is open or about to be.
+    pub fn is_active(&self) -> bool {
+        !matches!(self.state, MicPhase::Idle | MicPhase::Error)
+    }
+}
+
+/// Idle until the frontend has loaded and reported otherwise.
+static CURRENT: Mutex<MicState> = Mutex::new(MicState {
+    state: MicPhase::Idle,
+    mode: MicMode::None,
+    language: String::new(),
+});
+
+pub fn current() -> MicState {
+    CURRENT.lock().map(|mic| mic.clone()).unwrap_or(MicState {
+        state: MicPhase::Error,
+        mode: MicMode::None,
+        language: String::new(),
+    })
+}
+
+/// Start tracking the state the frontend reports.
+pub fn init(app: &App) {
+    app.listen("mic-state-changed", |event| {
+        match serde_json::from_str::<MicState>(event.payload()) {
+            Ok(mic) => {
+                if let Ok(mut current) = CURRENT.lock() {
+                    *current = mic;
+                }
+            }
+            Err(e) => eprintln!("[Mic] unexpected mic-state-changed payload: {e}"),
+        }
+    });
+}
@@ -1,38 +1,583 @@
-use tauri::{App, Emitter};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{App, AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
-pub fn register_shortcuts(app: &App) {
-    let handle = app.handle().clone();
+use crate::config::ShortcutsConfig;
+use crate::error::AppError;
+use crate::injection::InjectionQueue;
 
-    app.global_shortcut()
-        .on_shortcut("ctrl+shift+d", {
+mod capture;
+mod double_tap;
+
+pub use double_tap::TapModifier;
+
+/// How long a release must stand before it counts. X11 auto-repeat turns a
+/// held key into Released/Pressed pairs a few milliseconds apart.
+const RELEASE_DEBOUNCE: Duration = Duration::from_millis(40);
+
+/// How long `capture_shortcut` waits for a key combination.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the dictation shortcut behaves (`shortcuts.dictation_mode`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DictationMode {
+    /// Each press starts or stops dictation.
+    #[default]
+    Toggle,
+    /// Dictate while the shortcut is held down.
+    PushToTalk,
+}
+
+/// A bindable action: its key under `shortcuts:` in `config.yaml`, the
+/// event the frontend reacts to, and anything the Rust side has to do
+/// itself before the event goes out.
+struct ShortcutAction {
+    key: &'static str,
+    event: &'static str,
+    accelerator: fn(&ShortcutsConfig) -> &str,
+    before: Option<fn(&AppHandle)>,
+    /// Follows `shortcuts.dictation_mode`, so it can be held for
+    /// push-to-talk.
+    push_to_talk: bool,
+}
+
+const ACTIONS: &[ShortcutAction] = &[
+    ShortcutAction {
+        key: "toggle_dictation",
+        event: "shortcut:toggle-dictation",
+        accelerator: |c| &c.toggle_dictation,
+        before: Some(arm_target),
+        push_to_talk: true,
+    },
+    ShortcutAction {
+        key: "toggle_transcription",
+        event: "shortcut:toggle-transcription",
+        accelerator: |c| &c.toggle_transcription,
+        before: None,
+        push_to_talk: false,
+    },
+    ShortcutAction {
+        key: "undo_dictation",
+        event: "shortcut:undo-dictation",
+        accelerator: |c| &c.undo_dictation,
+        before: Some(undo_dictation),
+        push_to_talk: false,
+    },
+    ShortcutAction {
+        key: "cancel_session",
+        event: "shortcut:cancel-session",
+        accelerator: |c| &c.cancel_session,
+        before: Some(cancel_injection),
+        push_to_talk: false,
+    },
+    ShortcutAction {
+        key: "cycle_language",
+        event: "shortcut:cycle-language",
+        accelerator: |c| &c.cycle_language,
+        before: None,
+        push_to_talk: false,
+    },
+    ShortcutAction {
+        key: "toggle_overlay",
+        event: "shortcut:toggle-overlay",
+        accelerator: |c| &c.toggle_overlay,
+        before: Some(toggle_overlay),
+        push_to_talk: false,
+    },
+    ShortcutAction {
+        key: "open_main_window",
+        event: "shortcut:open-main-window",
+        accelerator: |c| &c.open_main_window,
+        before: Some(crate::tray::show_main_window),
+        push_to_talk: false,
+    },
+    ShortcutAction {
+        key: "paste_last_transcript",
+        event: "shortcut:paste-last-transcript",
+        accelerator: |c| &c.paste_last_transcript,
+        before: None,
+        push_to_talk: false,
+    },
+    ShortcutAction {
+        key: "pause_transcription",
+        event: "shortcut:pause-transcription",
+        accelerator: |c| &c.pause_transcription,
+        before: None,
+        push_to_talk: false,
+    },
+];
+
+impl ShortcutAction {
+    fn run(&self, handle: &AppHandle) {
+        if let Some(before) = self.before {
+            before(handle);
+        }
+        emit(handle, self.event);
+    }
+}
+
+/// Whether `key` names a bindable action.
+pub fn has_action(key: &str) -> bool {
+    ACTIONS.iter().any(|action| action.key == key)
+}
+
+/// Run a bindable action as if its shortcut had been pressed (tray menu).
+pub fn run_action(handle: &AppHandle, key: &str) {
+    match ACTIONS.iter().find(|action| action.key == key) {
+        Some(action) => action.run(handle),
+        None => eprintln!("[Shortcuts] unknown action {key}"),
+    }
+}
+
+/// Capture the target window before our own windows react.
+fn arm_target(handle: &AppHandle) {
+    handle.state::<InjectionQueue>().arm_target();
+}
+
+fn undo_dictation(handle: &AppHandle) {
+    if let Err(e) = handle.state::<InjectionQueue>().undo_session() {
+        eprintln!("[Shortcuts] undo error: {e}");
+    }
+}
+
+/// Drop whatever the session still had queued, so nothing more is typed
+/// while the frontend tears the session down.
+fn cancel_injection(handle: &AppHandle) {
+    let queue = handle.state::<InjectionQueue>();
+    let pending = queue.cancel();
+    queue.end_session();
+    println!("[Shortcuts] session cancelled, {pending} injection(s) dropped");
+}
+
+fn toggle_overlay(handle: &AppHandle) {
+    let Some(overlay) = handle.get_webview_window("overlay") else {
+        return;
+    };
+    let result = if overlay.is_visible().unwrap_or(false) {
+        overlay.hide()
+    } else {
+        overlay.show()
+    };
+    if let Err(e) = result {
+        eprintln!("[Shortcuts] failed to toggle overlay: {e}");
+    }
+}
+
+/// What a registered shortcut does.
+#[derive(Clone)]
+enum Trigger {
+    Action(&'static ShortcutAction),
+    /// Start or stop dictation in a fixed language
+    /// (`shortcuts.language_dictation`).
+    Dictation {
+        language: String,
+    },
+}
+
+impl Trigger {
+    fn run(&self, handle: &AppHandle) {
+        match self {
+            Trigger::Action(action) => action.run(handle),
+            Trigger::Dictation { language } => {
+                arm_target(handle);
+                emit_with(
+                    handle,
+                    "shortcut:toggle-dictation",
+                    SessionRequest {
+                        language: Some(language.clone()),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Payload of the session events that start in a given language
+/// (language-specific shortcuts, `openwhisper start-transcription --lang`).
+#[derive(Clone, Serialize)]
+pub struct SessionRequest {
+    pub language: Option<String>,
+}
+
+fn emit(handle: &AppHandle, event: &str) {
+    emit_with(handle, event, ());
+}
+
+pub fn emit_with<S: Serialize + Clone>(handle: &AppHandle, event: &str, payload: S) {
+    match handle.emit(event, payload) {
+        Ok(_) => println!("[Shortcuts] emitted {event}"),
+        Err(e) => eprintln!("[Shortcuts] emit {event} error: {e}"),
+    }
+}
+
+/// Where the push-to-talk key is. `toggle_dictation` and every
+/// `language_dictation` binding share it, as they drive the one dictation
+/// session: while one of them is held, presses of the others are ignored.
+struct Hold {
+    /// The key is down (auto-repeat presses are ignored while it is).
+    held: bool,
+    /// A release was seen and is waiting out `RELEASE_DEBOUNCE`.
+    release_pending: bool,
+    /// Dictation was started for the current press.
+    started: bool,
+    /// Bumped on every genuine press, so timers from an older press do
+    /// nothing.
+    press: u64,
+    /// Bumped on every event, so a pending release can tell whether the key
+    /// came back down in the meantime.
+    seq: u64,
+}
+
+static HOLD: Mutex<Hold> = Mutex::new(Hold {
+    held: false,
+    release_pending: false,
+    started: false,
+    press: 0,
+    seq: 0,
+});
+
+fn push_to_talk(
+    handle: &AppHandle,
+    state: ShortcutState,
+    min_hold: Duration,
+    language: Option<String>,
+) {
+    let Ok(mut hold) = HOLD.lock() else {
+        return;
+    };
+    hold.seq += 1;
+    match state {
+        ShortcutState::Pressed => {
+            hold.release_pending = false;
+            if hold.held {
+                // Auto-repeat, or the Pressed half of a repeat pair
+                return;
+            }
+            hold.held = true;
+            hold.started = false;
+            hold.press += 1;
+            let press = hold.press;
+            drop(hold);
+
+            println!("[Shortcuts] push-to-talk pressed");
+            handle.state::<InjectionQueue>().arm_target();
             let handle = handle.clone();
-            move |_app, _shortcut, event| {
-                if event.state == ShortcutState::Pressed {
-                    println!("[Shortcuts] Ctrl+Shift+D pressed, emitting toggle-dictation");
-                    match handle.emit("shortcut:toggle-dictation", ()) {
-                        Ok(_) => println!("[Shortcuts] emit OK"),
-                        Err(e) => eprintln!("[Shortcuts] emit error: {e}"),
+            thread::spawn(move || {
+                thread::sleep(min_hold);
+                loop {
+                    let Ok(mut hold) = HOLD.lock() else {
+                        return;
+                    };
+                    // Too short a tap opens no session at all
+                    if hold.press != press || !hold.held {
+                        return;
+                    }
+                    if hold.release_pending {
+                        // Maybe the Released half of a repeat pair: look
+                        // again once the release has been confirmed or not
+                        drop(hold);
+                        thread::sleep(RELEASE_DEBOUNCE);
+                        continue;
                     }
+                    hold.started = true;
+                    drop(hold);
+                    emit_with(
+                        &handle,
+                        "shortcut:dictation-start",
+                        SessionRequest { language },
+                    );
+                    return;
+                }
+            });
+        }
+        ShortcutState::Released => {
+            hold.release_pending = true;
+            let seq = hold.seq;
+            drop(hold);
+
+            let handle = handle.clone();
+            thread::spawn(move || {
+                thread::sleep(RELEASE_DEBOUNCE);
+                let Ok(mut hold) = HOLD.lock() else {
+                    return;
+                };
+                if hold.seq != seq {
+                    return;
                 }
+                hold.held = false;
+                hold.release_pending = false;
+                let started = std::mem::take(&mut hold.started);
+                drop(hold);
+
+                println!("[Shortcuts] push-to-talk released");
+                if started {
+                    emit(&handle, "shortcut:dictation-stop");
+                }
+            });
+        }
+    }
+}
+
+/// Outcome of registering one binding, shown next to it in the settings UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct BindingResult {
+    /// Config key under `shortcuts:` (`language_dictation.<code>` for
+    /// language shortcuts).
+    action: String,
+    accelerator: String,
+    #[serde(flatten)]
+    status: BindingStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum BindingStatus {
+    Registered,
+    /// Not bound yet, and free to bind (`capture_shortcut` only).
+    Available,
+    /// No accelerator configured; the action is unbound.
+    Disabled,
+    /// The accelerator could not be parsed.
+    Invalid {
+        message: String,
+    },
+    /// An earlier binding in the config already uses this accelerator.
+    Duplicate {
+        other: String,
+    },
+    /// The OS refused the grab, usually because another app holds it.
+    InUse {
+        message: String,
+    },
+    /// The double-tap key listener could not be started.
+    Unavailable {
+        message: String,
+    },
+}
+
+/// Results of the last registration pass, for `get_shortcut_status`.
+#[derive(Default)]
+pub struct ShortcutRegistry(Mutex<Vec<BindingResult>>);
+
+/// Replace every global shortcut with the bindings in `config`. Failures
+/// are reported per binding; none of them is fatal.
+fn register_all(handle: &AppHandle, config: &ShortcutsConfig) -> Vec<BindingResult> {
+    if let Err(e) = handle.global_shortcut().unregister_all() {
+        eprintln!("[Shortcuts] failed to unregister previous shortcuts: {e}");
+    }
+    let double_tap = BindingResult {
+        action: "double_tap_modifier".to_string(),
+        accelerator: config
+            .double_tap_modifier
+            .map(|m| format!("{m:?}"))
+            .unwrap_or_default(),
+        status: double_tap::configure(handle, config),
+    };
+
+    let hold = match config.dictation_mode {
+        DictationMode::Toggle => None,
+        DictationMode::PushToTalk => Some(Duration::from_millis(config.push_to_talk_min_hold_ms)),
+    };
+    let bindings = ACTIONS
+        .iter()
+        .map(|action| {
+            let hold = hold.filter(|_| action.push_to_talk);
+            let accelerator = (action.accelerator)(config);
+            (
+                action.key.to_string(),
+                accelerator,
+                Trigger::Action(action),
+                hold,
+            )
+        })
+        .chain(
+            config
+                .language_dictation
+                .iter()
+                .map(|(language, accelerator)| {
+                    let trigger = Trigger::Dictation {
+                        language: language.clone(),
+                    };
+                    (
+                        format!("language_dictation.{language}"),
+                        accelerator.as_str(),
+                        trigger,
+                        hold,
+                    )
+                }),
+        );
+
+    let mut taken: Vec<(Shortcut, String)> = Vec::new();
+    bindings
+        .map(|(name, accelerator, trigger, hold)| {
+            let status = register(handle, &name, accelerator, trigger, hold, &mut taken);
+            match &status {
+                BindingStatus::Registered => {
+                    println!("[Shortcuts] {name}: {accelerator} registered")
+                }
+                other => eprintln!("[Shortcuts] {name}: {accelerator:?} not registered: {other:?}"),
+            }
+            BindingResult {
+                action: name,
+                accelerator: accelerator.to_string(),
+                status,
             }
         })
-        .expect("failed to register Ctrl+Shift+D");
+        .chain(std::iter::once(double_tap))
+        .collect()
+}
 
-    app.global_shortcut()
-        .on_shortcut("ctrl+shift+t", {
-            let handle = handle.clone();
-            move |_app, _shortcut, event| {
-                if event.state == ShortcutState::Pressed {
-                    println!("[Shortcuts] Ctrl+Shift+T pressed, emitting toggle-transcription");
-                    match handle.emit("shortcut:toggle-transcription", ()) {
-                        Ok(_) => println!("[Shortcuts] emit OK"),
-                        Err(e) => eprintln!("[Shortcuts] emit error: {e}"),
-                    }
+fn register(
+    handle: &AppHandle,
+    name: &str,
+    accelerator: &str,
+    trigger: Trigger,
+    hold: Option<Duration>,
+    taken: &mut Vec<(Shortcut, String)>,
+) -> BindingStatus {
+    let accelerator = accelerator.trim();
+    if accelerator.is_empty() {
+        return BindingStatus::Disabled;
+    }
+    let shortcut = match Shortcut::from_str(accelerator) {
+        Ok(shortcut) => shortcut,
+        Err(e) => {
+            return BindingStatus::Invalid {
+                message: e.to_string(),
+            }
+        }
+    };
+    if let Some((_, other)) = taken.iter().find(|(s, _)| *s == shortcut) {
+        return BindingStatus::Duplicate {
+            other: other.clone(),
+        };
+    }
+
+    let language = match &trigger {
+        Trigger::Dictation { language } => Some(language.clone()),
+        Trigger::Action(_) => None,
+    };
+    let log_name = name.to_string();
+    let result = handle
+        .global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, event| {
+            if capture::in_progress() {
+                return;
+            }
+            match hold {
+                Some(min_hold) => push_to_talk(app, event.state, min_hold, language.clone()),
+                None if event.state == ShortcutState::Pressed => {
+                    println!("[Shortcuts] {log_name} pressed");
+                    trigger.run(app);
                 }
+                None => {}
             }
+        });
+    match result {
+        Ok(()) => {
+            taken.push((shortcut, name.to_string()));
+            BindingStatus::Registered
+        }
+        Err(e) => BindingStatus::InUse {
+            message: e.to_string(),
+        },
+    }
+}
+
+pub fn register_shortcuts(app: &App, config: &ShortcutsConfig) {
+    let results = register_all(app.handle(), config);
+    app.manage(ShortcutRegistry(Mutex::new(results)));
+}
+
+/// Re-register the global shortcuts after the settings changed.
+#[tauri::command]
+pub fn apply_shortcuts(
+    shortcuts: ShortcutsConfig,
+    app: AppHandle,
+    registry: State<'_, ShortcutRegistry>,
+) -> Result<Vec<BindingResult>, AppError> {
+    let results = register_all(&app, &shortcuts);
+    *registry.0.lock()? = results.clone();
+    Ok(results)
+}
+
+/// Per-binding results of the last registration.
+#[tauri::command]
+pub fn get_shortcut_status(
+    registry: State<'_, ShortcutRegistry>,
+) -> Result<Vec<BindingResult>, AppError> {
+    Ok(registry.0.lock()?.clone())
+}
+
+/// Record the next key combination pressed, to bind it to `action`.
+/// Resolves to `None` if Escape is pressed or nothing is pressed before
+/// the timeout. The accelerator comes back normalized ("Ctrl+Shift+D")
+/// with whether it can be bound.
+#[tauri::command]
+pub async fn capture_shortcut(
+    action: String,
+    app: AppHandle,
+) -> Result<Option<BindingResult>, AppError> {
+    let captured =
+        tauri::async_runtime::spawn_blocking(|| capture::next_combination(CAPTURE_TIMEOUT))
+            .await
+            .map_err(|e| AppError::ShortcutCaptureFailed {
+                message: format!("Shortcut capture thread failed: {e}"),
+            })??;
+    let Some(shortcut) = captured else {
+        println!("[Shortcuts] capture for {action} cancelled");
+        return Ok(None);
+    };
+
+    let accelerator = capture::accelerator(&shortcut);
+    let status = availability(&app, &action, shortcut)?;
+    println!("[Shortcuts] captured {accelerator} for {action}: {status:?}");
+    Ok(Some(BindingResult {
+        action,
+        accelerator,
+        status,
+    }))
+}
+
+/// Whether `shortcut` can be bound to `action`: not taken by another of
+/// our bindings, and not grabbed by another app.
+fn availability(
+    handle: &AppHandle,
+    action: &str,
+    shortcut: Shortcut,
+) -> Result<BindingStatus, AppError> {
+    let registry = handle.state::<ShortcutRegistry>();
+    let owner = registry
+        .0
+        .lock()?
+        .iter()
+        .find(|r| {
+            matches!(r.status, BindingStatus::Registered)
+                && Shortcut::from_str(r.accelerator.trim()).is_ok_and(|s| s == shortcut)
         })
-        .expect("failed to register Ctrl+Shift+T");
+        .map(|r| r.action.clone());
+    match owner {
+        Some(owner) if owner == action => return Ok(BindingStatus::Registered),
+        Some(other) => return Ok(BindingStatus::Duplicate { other }),
+        None => {}
+    }
 
-    println!("[Shortcuts] all shortcuts registered");
+    // Registering is the only way to find out whether the OS lets us have it
+    let global = handle.global_shortcut();
+    match global.register(shortcut) {
+        Ok(()) => {
+            if let Err(e) = global.unregister(shortcut) {
+                eprintln!("[Shortcuts] failed to release {shortcut} after check: {e}");
+            }
+            Ok(BindingStatus::Available)
+        }
+        Err(e) => Ok(BindingStatus::InUse {
+            message: e.to_string(),
+        }),
+    }
 }
@@ -0,0 +1,464 @@
+//! Recording the next key combination pressed, so shortcuts can be set by
+//! pressing them instead of typing accelerator strings.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri_plugin_global_shortcut::{Modifiers, Shortcut};
+
+#[cfg(any(windows, target_os = "linux"))]
+use tauri_plugin_global_shortcut::Code;
+
+use crate::error::AppError;
+
+#[cfg(any(windows, target_os = "linux"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Set while a combination is being recorded; the app's own shortcuts do
+/// nothing meanwhile, so their current binding can be recorded too.
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+pub fn in_progress() -> bool {
+    CAPTURING.load(Ordering::SeqCst)
+}
+
+struct CaptureGuard;
+
+impl Drop for CaptureGuard {
+    fn drop(&mut self) {
+        CAPTURING.store(false, Ordering::SeqCst);
+    }
+}
+
+fn failed(message: impl Into<String>) -> AppError {
+    AppError::ShortcutCaptureFailed {
+        message: message.into(),
+    }
+}
+
+/// Wait for a key combination with at least one non-modifier key. `None`
+/// if Escape is pressed on its own or nothing is pressed within `timeout`.
+pub fn next_combination(timeout: Duration) -> Result<Option<Shortcut>, AppError> {
+    if CAPTURING.swap(true, Ordering::SeqCst) {
+        return Err(failed("Already recording a shortcut"));
+    }
+    let _guard = CaptureGuard;
+    platform::next_combination(timeout)
+}
+
+/// `shortcut` in the accelerator syntax of `config.yaml`, modifiers in a
+/// fixed order: "Ctrl+Shift+D", "Alt+F9".
+pub fn accelerator(shortcut: &Shortcut) -> String {
+    let mut parts = Vec::new();
+    for (modifier, name) in [
+        (Modifiers::CONTROL, "Ctrl"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::SHIFT, "Shift"),
+        (Modifiers::SUPER, "Super"),
+    ] {
+        if shortcut.mods.contains(modifier) {
+            parts.push(name.to_string());
+        }
+    }
+    // "KeyD" and "Digit1" parse back from "D" and "1"
+    let key = shortcut.key.to_string();
+    let key = match key
+        .strip_prefix("Key")
+        .or_else(|| key.strip_prefix("Digit"))
+    {
+        Some(short) if short.len() == 1 => short.to_string(),
+        _ => key,
+    };
+    parts.push(key);
+    parts.join("+")
+}
+
+/// Escape without modifiers cancels the recording.
+#[cfg(any(windows, target_os = "linux"))]
+fn finish(mods: Modifiers, key: Code) -> Option<Shortcut> {
+    (key != Code::Escape || !mods.is_empty()).then(|| Shortcut::new(Some(mods), key))
+}
+
+#[cfg(any(windows, target_os = "linux"))]
+const LETTERS: [Code; 26] = [
+    Code::KeyA,
+    Code::KeyB,
+    Code::KeyC,
+    Code::KeyD,
+    Code::KeyE,
+    Code::KeyF,
+    Code::KeyG,
+    Code::KeyH,
+    Code::KeyI,
+    Code::KeyJ,
+    Code::KeyK,
+    Code::KeyL,
+    Code::KeyM,
+    Code::KeyN,
+    Code::KeyO,
+    Code::KeyP,
+    Code::KeyQ,
+    Code::KeyR,
+    Code::KeyS,
+    Code::KeyT,
+    Code::KeyU,
+    Code::KeyV,
+    Code::KeyW,
+    Code::KeyX,
+    Code::KeyY,
+    Code::KeyZ,
+];
+
+#[cfg(any(windows, target_os = "linux"))]
+const DIGITS: [Code; 10] = [
+    Code::Digit0,
+    Code::Digit1,
+    Code::Digit2,
+    Code::Digit3,
+    Code::Digit4,
+    Code::Digit5,
+    Code::Digit6,
+    Code::Digit7,
+    Code::Digit8,
+    Code::Digit9,
+];
+
+#[cfg(any(windows, target_os = "linux"))]
+const NUMPAD: [Code; 10] = [
+    Code::Numpad0,
+    Code::Numpad1,
+    Code::Numpad2,
+    Code::Numpad3,
+    Code::Numpad4,
+    Code::Numpad5,
+    Code::Numpad6,
+    Code::Numpad7,
+    Code::Numpad8,
+    Code::Numpad9,
+];
+
+#[cfg(any(windows, target_os = "linux"))]
+const FUNCTION_KEYS: [Code; 24] = [
+    Code::F1,
+    Code::F2,
+    Code::F3,
+    Code::F4,
+    Code::F5,
+    Code::F6,
+    Code::F7,
+    Code::F8,
+    Code::F9,
+    Code::F10,
+    Code::F11,
+    Code::F12,
+    Code::F13,
+    Code::F14,
+    Code::F15,
+    Code::F16,
+    Code::F17,
+    Code::F18,
+    Code::F19,
+    Code::F20,
+    Code::F21,
+    Code::F22,
+    Code::F23,
+    Code::F24,
+];
+
+/// Polls the asynchronous key state: unlike a keyboard hook this needs no
+/// message loop, and keys taken by `RegisterHotKey` still show up.
+#[cfg(windows)]
+mod platform {
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+
+    use super::{finish, DIGITS, FUNCTION_KEYS, LETTERS, NUMPAD, POLL_INTERVAL};
+    use crate::error::AppError;
+
+    fn is_down(vk: VIRTUAL_KEY) -> bool {
+        unsafe { GetAsyncKeyState(vk as i32) < 0 }
+    }
+
+    fn modifiers() -> Modifiers {
+        let mut mods = Modifiers::empty();
+        if is_down(VK_CONTROL) {
+            mods |= Modifiers::CONTROL;
+        }
+        if is_down(VK_MENU) {
+            mods |= Modifiers::ALT;
+        }
+        if is_down(VK_SHIFT) {
+            mods |= Modifiers::SHIFT;
+        }
+        if is_down(VK_LWIN) || is_down(VK_RWIN) {
+            mods |= Modifiers::SUPER;
+        }
+        mods
+    }
+
+    /// Modifiers, mouse buttons and keys the accelerator syntax has no
+    /// name for map to `None`.
+    fn code(vk: VIRTUAL_KEY) -> Option<Code> {
+        Some(match vk {
+            0x41..=0x5A => LETTERS[(vk - 0x41) as usize],
+            0x30..=0x39 => DIGITS[(vk - 0x30) as usize],
+            VK_NUMPAD0..=VK_NUMPAD9 => NUMPAD[(vk - VK_NUMPAD0) as usize],
+            VK_F1..=VK_F24 => FUNCTION_KEYS[(vk - VK_F1) as usize],
+            VK_BACK => Code::Backspace,
+            VK_TAB => Code::Tab,
+            VK_RETURN => Code::Enter,
+            VK_ESCAPE => Code::Escape,
+            VK_SPACE => Code::Space,
+            VK_PRIOR => Code::PageUp,
+            VK_NEXT => Code::PageDown,
+            VK_END => Code::End,
+            VK_HOME => Code::Home,
+            VK_LEFT => Code::ArrowLeft,
+            VK_UP => Code::ArrowUp,
+            VK_RIGHT => Code::ArrowRight,
+            VK_DOWN => Code::ArrowDown,
+            VK_INSERT => Code::Insert,
+            VK_DELETE => Code::Delete,
+            VK_SNAPSHOT => Code::PrintScreen,
+            VK_PAUSE => Code::Pause,
+            VK_SCROLL => Code::ScrollLock,
+            VK_MULTIPLY => Code::NumpadMultiply,
+            VK_ADD => Code::NumpadAdd,
+            VK_SUBTRACT => Code::NumpadSubtract,
+            VK_DECIMAL => Code::NumpadDecimal,
+            VK_DIVIDE => Code::NumpadDivide,
+            VK_OEM_1 => Code::Semicolon,
+            VK_OEM_PLUS => Code::Equal,
+            VK_OEM_COMMA => Code::Comma,
+            VK_OEM_MINUS => Code::Minus,
+            VK_OEM_PERIOD => Code::Period,
+            VK_OEM_2 => Code::Slash,
+            VK_OEM_3 => Code::Backquote,
+            VK_OEM_4 => Code::BracketLeft,
+            VK_OEM_5 => Code::Backslash,
+            VK_OEM_6 => Code::BracketRight,
+            VK_OEM_7 => Code::Quote,
+            VK_VOLUME_MUTE => Code::AudioVolumeMute,
+            VK_VOLUME_DOWN => Code::AudioVolumeDown,
+            VK_VOLUME_UP => Code::AudioVolumeUp,
+            VK_MEDIA_NEXT_TRACK => Code::MediaTrackNext,
+            VK_MEDIA_PREV_TRACK => Code::MediaTrackPrevious,
+            VK_MEDIA_STOP => Code::MediaStop,
+            VK_MEDIA_PLAY_PAUSE => Code::MediaPlayPause,
+            _ => return None,
+        })
+    }
+
+    pub fn next_combination(timeout: Duration) -> Result<Option<Shortcut>, AppError> {
+        let deadline = Instant::now() + timeout;
+        // Keys already down (e.g. Enter on the record button) don't count
+        let mut was_down: Vec<bool> = (0..=255).map(is_down).collect();
+        while Instant::now() < deadline {
+            for vk in 1..=255 {
+                let down = is_down(vk);
+                let pressed = down && !was_down[vk as usize];
+                was_down[vk as usize] = down;
+                if let Some(key) = code(vk).filter(|_| pressed) {
+                    return Ok(finish(modifiers(), key));
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        Ok(None)
+    }
+}
+
+/// Grabs the whole keyboard on the root window, so the combination reaches
+/// no other app (and none of the app's own passive grabs).
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        ConnectionExt as _, GetKeyboardMappingReply, GrabMode, GrabStatus, KeyButMask, Keycode,
+        Keysym,
+    };
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::CURRENT_TIME;
+
+    use super::{failed, finish, DIGITS, FUNCTION_KEYS, LETTERS, NUMPAD, POLL_INTERVAL};
+    use crate::error::AppError;
+
+    /// Another client (often the window manager, for a moment after a
+    /// click) may hold the keyboard; retry for this long.
+    const GRAB_RETRY: Duration = Duration::from_millis(500);
+
+    fn is_modifier(keysym: Keysym) -> bool {
+        // Shift_L..Hyper_R (incl. Caps_Lock), ISO_Level3_Shift,
+        // Mode_switch, Num_Lock
+        matches!(keysym, 0xffe1..=0xffee | 0xfe03 | 0xff7e | 0xff7f)
+    }
+
+    fn code(keysym: Keysym) -> Option<Code> {
+        Some(match keysym {
+            0x61..=0x7a => LETTERS[(keysym - 0x61) as usize],
+            0x30..=0x39 => DIGITS[(keysym - 0x30) as usize],
+            0xffb0..=0xffb9 => NUMPAD[(keysym - 0xffb0) as usize],
+            0xffbe..=0xffd5 => FUNCTION_KEYS[(keysym - 0xffbe) as usize],
+            0xff08 => Code::Backspace,
+            0xff09 => Code::Tab,
+            0xff0d => Code::Enter,
+            0xff13 => Code::Pause,
+            0xff14 => Code::ScrollLock,
+            0xff1b => Code::Escape,
+            0xff50 => Code::Home,
+            0xff51 => Code::ArrowLeft,
+            0xff52 => Code::ArrowUp,
+            0xff53 => Code::ArrowRight,
+            0xff54 => Code::ArrowDown,
+            0xff55 => Code::PageUp,
+            0xff56 => Code::PageDown,
+            0xff57 => Code::End,
+            0xff61 => Code::PrintScreen,
+            0xff63 => Code::Insert,
+            0xff8d => Code::NumpadEnter,
+            0xffaa => Code::NumpadMultiply,
+            0xffab => Code::NumpadAdd,
+            0xffad => Code::NumpadSubtract,
+            0xffae => Code::NumpadDecimal,
+            0xffaf => Code::NumpadDivide,
+            0xffff => Code::Delete,
+            0x20 => Code::Space,
+            0x27 => Code::Quote,
+            0x2c => Code::Comma,
+            0x2d => Code::Minus,
+            0x2e => Code::Period,
+            0x2f => Code::Slash,
+            0x3b => Code::Semicolon,
+            0x3d => Code::Equal,
+            0x5b => Code::BracketLeft,
+            0x5c => Code::Backslash,
+            0x5d => Code::BracketRight,
+            0x60 => Code::Backquote,
+            0x1008ff11 => Code::AudioVolumeDown,
+            0x1008ff12 => Code::AudioVolumeMute,
+            0x1008ff13 => Code::AudioVolumeUp,
+            0x1008ff14 => Code::MediaPlayPause,
+            0x1008ff15 => Code::MediaStop,
+            0x1008ff16 => Code::MediaTrackPrevious,
+            0x1008ff17 => Code::MediaTrackNext,
+            _ => return None,
+        })
+    }
+
+    fn modifiers(state: KeyButMask) -> Modifiers {
+        let mut mods = Modifiers::empty();
+        for (mask, modifier) in [
+            (KeyButMask::CONTROL, Modifiers::CONTROL),
+            (KeyButMask::MOD1, Modifiers::ALT),
+            (KeyButMask::SHIFT, Modifiers::SHIFT),
+            (KeyButMask::MOD4, Modifiers::SUPER),
+        ] {
+            if state.contains(mask) {
+                mods |= modifier;
+            }
+        }
+        mods
+    }
+
+    fn grab(conn: &RustConnection, root: u32) -> Result<(), AppError> {
+        let deadline = Instant::now() + GRAB_RETRY;
+        loop {
+            let status = conn
+                .grab_keyboard(false, root, CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)
+                .map_err(|e| failed(format!("Failed to grab the keyboard: {e}")))?
+                .reply()
+                .map_err(|e| failed(format!("Failed to grab the keyboard: {e}")))?
+                .status;
+            if status == GrabStatus::SUCCESS {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(failed(format!(
+                    "Another application holds the keyboard ({status:?})"
+                )));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Keysyms bound to `keycode`, unshifted first.
+    fn keysyms(mapping: &GetKeyboardMappingReply, min: Keycode, keycode: Keycode) -> &[Keysym] {
+        let per = mapping.keysyms_per_keycode as usize;
+        let start = keycode.saturating_sub(min) as usize * per;
+        mapping.keysyms.get(start..start + per).unwrap_or_default()
+    }
+
+    fn wait(
+        conn: &RustConnection,
+        mapping: &GetKeyboardMappingReply,
+        min: Keycode,
+        deadline: Instant,
+    ) -> Result<Option<Shortcut>, AppError> {
+        while Instant::now() < deadline {
+            let event = conn
+                .poll_for_event()
+                .map_err(|e| AppError::no_display(format!("X connection lost: {e}")))?;
+            let Some(event) = event else {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            };
+            let Event::KeyPress(press) = event else {
+                continue;
+            };
+            let syms = keysyms(mapping, min, press.detail);
+            if syms.first().copied().is_some_and(is_modifier) {
+                continue;
+            }
+            // Keypad digits sit in the second column (the first is KP_Home...)
+            if let Some(key) = syms.iter().take(2).find_map(|&sym| code(sym)) {
+                return Ok(finish(modifiers(press.state), key));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn next_combination(timeout: Duration) -> Result<Option<Shortcut>, AppError> {
+        if crate::injection::is_wayland_session() {
+            return Err(failed(
+                "Recording shortcuts is not supported on Wayland; type the shortcut instead",
+            ));
+        }
+        let deadline = Instant::now() + timeout;
+        let (conn, screen) = x11rb::connect(None)
+            .map_err(|e| AppError::no_display(format!("Failed to connect to X server: {e}")))?;
+        let setup = conn.setup();
+        let root = setup.roots[screen].root;
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let mapping = conn
+            .get_keyboard_mapping(min, max - min + 1)
+            .map_err(|e| failed(format!("Failed to read the keyboard mapping: {e}")))?
+            .reply()
+            .map_err(|e| failed(format!("Failed to read the keyboard mapping: {e}")))?;
+
+        grab(&conn, root)?;
+        let result = wait(&conn, &mapping, min, deadline);
+        let _ = conn.ungrab_keyboard(CURRENT_TIME);
+        let _ = conn.flush();
+        result
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    use std::time::Duration;
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    use super::failed;
+    use crate::error::AppError;
+
+    pub fn next_combination(_timeout: Duration) -> Result<Option<Shortcut>, AppError> {
+        Err(failed(
+            "Recording shortcuts is not supported on this platform; type the shortcut instead",
+        ))
+    }
+}
@@ -0,0 +1,376 @@
+//! Toggling dictation by double-tapping a lone modifier such as Right Ctrl,
+//! which accelerators cannot express. Needs a listener that sees every key
+//! press and release; it is only started once the trigger is enabled.
+
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use super::{capture, run_action, BindingStatus};
+use crate::config::ShortcutsConfig;
+use crate::error::AppError;
+
+/// How long `configure` waits for the listener to report it is running.
+const LISTEN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Modifier that toggles dictation when tapped twice
+/// (`shortcuts.double_tap_modifier`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TapModifier {
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+    LeftShift,
+    RightShift,
+    LeftSuper,
+    RightSuper,
+}
+
+#[derive(Clone, Copy)]
+struct Settings {
+    modifier: TapModifier,
+    /// Longest a tap may be held, and longest gap between the two taps.
+    interval: Duration,
+}
+
+/// `None` while the trigger is off. The listener keeps running once
+/// started and ignores keys meanwhile.
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+
+static TAPS: Mutex<Taps> = Mutex::new(Taps {
+    held: false,
+    down_since: None,
+    last_tap: None,
+});
+
+/// Set while a listener thread is starting or running; cleared when it
+/// fails, so the next `configure` can try again.
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+/// Set once a listener is running.
+static HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+struct Taps {
+    /// The modifier is down (auto-repeat presses are ignored while it is).
+    held: bool,
+    /// When the modifier went down, unless another key was pressed since,
+    /// which makes it a chord rather than a tap.
+    down_since: Option<Instant>,
+    /// When the previous tap ended.
+    last_tap: Option<Instant>,
+}
+
+impl Taps {
+    /// Feed one key event; true when it completes a double tap.
+    fn key(&mut self, is_modifier: bool, pressed: bool, now: Instant, interval: Duration) -> bool {
+        if !is_modifier {
+            if pressed {
+                self.down_since = None;
+                self.last_tap = None;
+            }
+            return false;
+        }
+        if pressed {
+            if !self.held {
+                self.held = true;
+                self.down_since = Some(now);
+            }
+            return false;
+        }
+
+        self.held = false;
+        let Some(since) = self.down_since.take() else {
+            return false;
+        };
+        if now - since > interval {
+            // Held rather than tapped
+            self.last_tap = None;
+            return false;
+        }
+        match self.last_tap.take() {
+            Some(previous) if now - previous <= interval => true,
+            _ => {
+                self.last_tap = Some(now);
+                false
+            }
+        }
+    }
+}
+
+/// Apply `shortcuts.double_tap_*`, starting the key listener the first
+/// time the trigger is enabled. Reports whether the trigger works.
+pub(super) fn configure(handle: &AppHandle, config: &ShortcutsConfig) -> BindingStatus {
+    let settings = config.double_tap_modifier.map(|modifier| Settings {
+        modifier,
+        interval: Duration::from_millis(config.double_tap_interval_ms),
+    });
+    if let Ok(mut current) = SETTINGS.lock() {
+        *current = settings;
+    }
+    let Some(settings) = settings else {
+        return BindingStatus::Disabled;
+    };
+    println!(
+        "[Shortcuts] double tap: {:?} within {:?}",
+        settings.modifier, settings.interval
+    );
+
+    if LISTENING.swap(true, Ordering::SeqCst) {
+        return BindingStatus::Registered;
+    }
+    match start_listener(handle.clone()) {
+        Ok(()) => BindingStatus::Registered,
+        Err(e) => BindingStatus::Unavailable {
+            message: e.to_string(),
+        },
+    }
+}
+
+/// Spawn the listener and wait until it is running or has failed.
+fn start_listener(handle: AppHandle) -> Result<(), AppError> {
+    let (started_tx, started_rx) = mpsc::channel();
+    let failed_tx = started_tx.clone();
+    thread::spawn(move || {
+        let result = platform::listen(move || {
+            let _ = HANDLE.set(handle);
+            let _ = started_tx.send(Ok(()));
+        });
+        LISTENING.store(false, Ordering::SeqCst);
+        match result {
+            Ok(()) => println!("[Shortcuts] double-tap listener stopped"),
+            Err(e) => {
+                eprintln!("[Shortcuts] double-tap listener unavailable: {e}");
+                let _ = failed_tx.send(Err(e));
+            }
+        }
+    });
+    started_rx.recv_timeout(LISTEN_TIMEOUT).unwrap_or_else(|_| {
+        eprintln!("[Shortcuts] double-tap listener did not start in time");
+        Err(AppError::input("The key listener did not start in time"))
+    })
+}
+
+/// Called by the platform listener for every key press and release.
+/// `modifier` is which tappable modifier the key is, if any.
+fn key_event(modifier: Option<TapModifier>, pressed: bool) {
+    let Some(settings) = SETTINGS.lock().ok().and_then(|s| *s) else {
+        return;
+    };
+    if crate::injection::is_typing() {
+        // Our own keystrokes, e.g. the Ctrl of a clipboard paste
+        return;
+    }
+    let is_modifier = modifier == Some(settings.modifier);
+    let Ok(mut taps) = TAPS.lock() else {
+        return;
+    };
+    if !taps.key(is_modifier, pressed, Instant::now(), settings.interval) {
+        return;
+    }
+    drop(taps);
+
+    if capture::in_progress() {
+        return;
+    }
+    let Some(handle) = HANDLE.get() else {
+        return;
+    };
+    println!("[Shortcuts] double tap of {:?}", settings.modifier);
+    run_action(handle, "toggle_dictation");
+}
+
+/// A low-level keyboard hook on a thread of its own, which pumps the
+/// messages the hook is called from.
+#[cfg(windows)]
+mod platform {
+    use std::ptr;
+    use windows_sys::Win32::Foundation::{GetLastError, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageW, SetWindowsHookExW, UnhookWindowsHookEx, HC_ACTION,
+        KBDLLHOOKSTRUCT, LLKHF_INJECTED, MSG, WH_KEYBOARD_LL, WM_KEYDOWN, WM_SYSKEYDOWN,
+    };
+
+    use super::{key_event, TapModifier};
+    use crate::error::AppError;
+
+    fn modifier(vk: VIRTUAL_KEY) -> Option<TapModifier> {
+        Some(match vk {
+            VK_LCONTROL => TapModifier::LeftCtrl,
+            VK_RCONTROL => TapModifier::RightCtrl,
+            VK_LMENU => TapModifier::LeftAlt,
+            VK_RMENU => TapModifier::RightAlt,
+            VK_LSHIFT => TapModifier::LeftShift,
+            VK_RSHIFT => TapModifier::RightShift,
+            VK_LWIN => TapModifier::LeftSuper,
+            VK_RWIN => TapModifier::RightSuper,
+            _ => return None,
+        })
+    }
+
+    unsafe extern "system" fn hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32 {
+            let info = &*(lparam as *const KBDLLHOOKSTRUCT);
+            // Our own injected keystrokes are not the user typing
+            if info.flags & LLKHF_INJECTED == 0 {
+                let pressed = matches!(wparam as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+                key_event(modifier(info.vkCode as VIRTUAL_KEY), pressed);
+            }
+        }
+        CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
+    }
+
+    pub fn listen(started: impl FnOnce()) -> Result<(), AppError> {
+        let hook = unsafe {
+            SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook), GetModuleHandleW(ptr::null()), 0)
+        };
+        if hook.is_null() {
+            return Err(AppError::input(format!(
+                "SetWindowsHookExW failed (error {})",
+                unsafe { GetLastError() }
+            )));
+        }
+        started();
+        let mut msg: MSG = unsafe { std::mem::zeroed() };
+        while unsafe { GetMessageW(&mut msg, ptr::null_mut(), 0, 0) } > 0 {}
+        unsafe { UnhookWindowsHookEx(hook) };
+        Ok(())
+    }
+}
+
+/// Records key events from every client through the RECORD extension,
+/// which needs no grab and does not interfere with other apps.
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::collections::HashMap;
+    use x11rb::connection::{Connection, RequestConnection};
+    use x11rb::protocol::record::{self, ConnectionExt as _};
+    use x11rb::protocol::xproto::{self, ConnectionExt as _, Keycode};
+
+    use super::{key_event, TapModifier};
+    use crate::error::AppError;
+
+    /// Category of intercepted protocol data (the others are bookkeeping).
+    const RECORD_FROM_SERVER: u8 = 0;
+
+    fn modifier(keysym: u32) -> Option<TapModifier> {
+        Some(match keysym {
+            0xffe3 => TapModifier::LeftCtrl,
+            0xffe4 => TapModifier::RightCtrl,
+            0xffe9 => TapModifier::LeftAlt,
+            // Alt_R, or ISO_Level3_Shift where Right Alt is AltGr
+            0xffea | 0xfe03 => TapModifier::RightAlt,
+            0xffe1 => TapModifier::LeftShift,
+            0xffe2 => TapModifier::RightShift,
+            0xffeb => TapModifier::LeftSuper,
+            0xffec => TapModifier::RightSuper,
+            _ => return None,
+        })
+    }
+
+    fn x_error(e: impl std::fmt::Display) -> AppError {
+        AppError::no_display(format!("X11 error: {e}"))
+    }
+
+    /// Which keycodes are tappable modifiers, from their unshifted keysym.
+    fn modifier_keycodes(
+        conn: &impl Connection,
+    ) -> Result<HashMap<Keycode, TapModifier>, AppError> {
+        let setup = conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let mapping = conn
+            .get_keyboard_mapping(min, max - min + 1)
+            .map_err(x_error)?
+            .reply()
+            .map_err(x_error)?;
+        let per = mapping.keysyms_per_keycode.max(1) as usize;
+        Ok(mapping
+            .keysyms
+            .chunks(per)
+            .zip(min..=max)
+            .filter_map(|(syms, keycode)| Some((keycode, modifier(*syms.first()?)?)))
+            .collect())
+    }
+
+    pub fn listen(started: impl FnOnce()) -> Result<(), AppError> {
+        if crate::injection::is_wayland_session() {
+            return Err(AppError::no_display(
+                "Wayland does not let apps see other windows' key presses",
+            ));
+        }
+        // One connection to control the recording, one to receive it
+        let (ctrl, _) = x11rb::connect(None).map_err(x_error)?;
+        let (data, _) = x11rb::connect(None).map_err(x_error)?;
+        if ctrl
+            .extension_information(record::X11_EXTENSION_NAME)
+            .map_err(x_error)?
+            .is_none()
+        {
+            return Err(AppError::no_display(
+                "The X server does not support the RECORD extension",
+            ));
+        }
+        let keycodes = modifier_keycodes(&ctrl)?;
+
+        let context = ctrl.generate_id().map_err(x_error)?;
+        let empty = record::Range8 { first: 0, last: 0 };
+        let empty_ext = record::ExtRange {
+            major: empty,
+            minor: record::Range16 { first: 0, last: 0 },
+        };
+        let range = record::Range {
+            core_requests: empty,
+            core_replies: empty,
+            ext_requests: empty_ext,
+            ext_replies: empty_ext,
+            delivered_events: empty,
+            device_events: record::Range8 {
+                first: xproto::KEY_PRESS_EVENT,
+                last: xproto::KEY_RELEASE_EVENT,
+            },
+            errors: empty,
+            client_started: false,
+            client_died: false,
+        };
+        ctrl.record_create_context(context, 0, &[record::CS::ALL_CLIENTS.into()], &[range])
+            .map_err(x_error)?
+            .check()
+            .map_err(x_error)?;
+
+        let replies = data.record_enable_context(context).map_err(x_error)?;
+        started();
+        for reply in replies {
+            let reply = reply.map_err(x_error)?;
+            if reply.client_swapped || reply.category != RECORD_FROM_SERVER {
+                continue;
+            }
+            // Device events are 32 bytes: type, then the keycode
+            for event in reply.data.chunks_exact(32) {
+                let pressed = match event[0] & 0x7f {
+                    xproto::KEY_PRESS_EVENT => true,
+                    xproto::KEY_RELEASE_EVENT => false,
+                    _ => continue,
+                };
+                key_event(keycodes.get(&event[1]).copied(), pressed);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    use crate::error::AppError;
+
+    pub fn listen(_started: impl FnOnce()) -> Result<(), AppError> {
+        Err(AppError::no_display(
+            "Double-tap shortcuts are not supported on this platform",
+        ))
+    }
+}
@@ -1,53 +1,78 @@
+use std::sync::Mutex;
 use tauri::{
-    menu::{CheckMenuItem, Menu, MenuItem, Submenu},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    App, Emitter, Listener, Manager, Wry,
+    App, AppHandle, Listener, Manager, Wry,
 };
 
-const LANGUAGES: &[(&str, &str)] = &[
-    ("fr", "Français"),
-    ("en", "English"),
-    ("es", "Español"),
-    ("de", "Deutsch"),
-    ("it", "Italiano"),
-    ("pt", "Português"),
-    ("nl", "Nederlands"),
-    ("pl", "Polski"),
-    ("ru", "Русский"),
-    ("zh", "中文"),
-    ("ja", "日本語"),
-    ("ko", "한국어"),
-    ("ar", "العربية"),
-];
+use crate::mic::{MicMode, MicPhase, MicState};
 
-fn update_lang_checks(items: &[(String, CheckMenuItem<Wry>)], selected: &str) {
-    for (code, item) in items {
-        let _ = item.set_checked(code == selected);
+mod backend_status;
+mod devices;
+mod icon;
+mod languages;
+mod models;
+mod recent;
+mod session;
+
+/// Id of the tray icon, for `AppHandle::tray_by_id`.
+const TRAY_ID: &str = "main";
+
+/// Check items of a submenu where one value is selected at a time, keyed
+/// by that value.
+type CheckItems = Mutex<Vec<(String, CheckMenuItem<Wry>)>>;
+
+/// Check the item for `selected` and uncheck the others. The items are
+/// copied out first: `set_checked` waits for the main thread, which may be
+/// waiting for the lock itself.
+fn check_only(items: &CheckItems, selected: &str) {
+    let Some(items) = items.lock().ok().map(|items| items.clone()) else {
+        return;
+    };
+    for (value, item) in &items {
+        let _ = item.set_checked(value == selected);
+    }
+}
+
+/// Bring the main window to the front, restoring it if minimized.
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
     }
 }
 
 pub fn create_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let sessions = session::create(app)?;
+    let backend_item = backend_status::create(app)?;
     let open_item = MenuItem::with_id(app, "open", "Open window", true, None::<&str>)?;
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
-    // Language submenu with check items
-    let mut lang_items: Vec<(String, CheckMenuItem<Wry>)> = Vec::new();
-    let lang_submenu = Submenu::with_id(app, "language", "Language", true)?;
-    for &(code, label) in LANGUAGES {
-        let item = CheckMenuItem::with_id(app, code, label, true, code == "fr", None::<&str>)?;
-        lang_submenu.append(&item)?;
-        lang_items.push((code.to_string(), item));
-    }
-
-    let menu = Menu::with_items(app, &[&open_item, &lang_submenu, &quit_item])?;
-
-    // Clone lang_items for use in closures (Tauri menu items are ref-counted)
-    let lang_items_for_menu = lang_items.clone();
-    let lang_items_for_listen = lang_items.clone();
+    let lang_submenu = languages::create(app)?;
+    let recent_submenu = recent::create(app)?;
+    let device_submenu = devices::create(app)?;
+    let model_submenu = models::create(app)?;
 
-    let lang_codes: Vec<String> = LANGUAGES.iter().map(|&(c, _)| c.to_string()).collect();
+    let menu = Menu::with_items(
+        app,
+        &[
+            &backend_item,
+            &PredefinedMenuItem::separator(app)?,
+            &sessions.dictation,
+            &sessions.transcription,
+            &PredefinedMenuItem::separator(app)?,
+            &open_item,
+            &recent_submenu,
+            &lang_submenu,
+            &device_submenu,
+            &model_submenu,
+            &PredefinedMenuItem::separator(app)?,
+            &quit_item,
+        ],
+    )?;
 
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .show_menu_on_left_click(false)
@@ -55,47 +80,51 @@ pub fn create_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .on_menu_event(move |app, event| {
             let id = event.id().as_ref();
             match id {
-                "open" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.unminimize();
-                        let _ = window.set_focus();
-                    }
+                // Same as the shortcuts, for when a hotkey is unavailable
+                session::DICTATION_ID => crate::shortcuts::run_action(app, "toggle_dictation"),
+                session::TRANSCRIPTION_ID => {
+                    crate::shortcuts::run_action(app, "toggle_transcription")
                 }
+                backend_status::ID => backend_status::on_click(app),
+                "open" => show_main_window(app),
                 "quit" => {
                     app.exit(0);
                 }
-                code if lang_codes.contains(&code.to_string()) => {
-                    update_lang_checks(&lang_items_for_menu, code);
-                    println!("[Tray] language changed to: {code}");
-                    let _ = app.emit("tray:language-changed", code.to_string());
-                }
+                id if languages::on_menu_event(app, id) => {}
+                id if recent::on_menu_event(app, id) => {}
+                id if devices::on_menu_event(app, id) => {}
+                id if models::on_menu_event(app, id) => {}
                 _ => {}
             }
         })
-        .on_tray_icon_event(|tray, event| {
-            if let TrayIconEvent::Click {
+        .on_tray_icon_event(|tray, event| match event {
+            TrayIconEvent::Click {
                 button: MouseButton::Left,
                 button_state: MouseButtonState::Up,
                 ..
-            } = event
-            {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.unminimize();
-                    let _ = window.set_focus();
-                }
-            }
+            } => show_main_window(tray.app_handle()),
+            // The menu is opening: pick up newly plugged-in devices
+            TrayIconEvent::Click {
+                button: MouseButton::Right,
+                button_state: MouseButtonState::Down,
+                ..
+            } => devices::rescan(tray.app_handle()),
+            _ => {}
         })
         .build(app)?;
 
-    // Listen for language changes from frontend to sync tray checkmarks
-    app.listen("language-changed", move |event| {
-        let code = event.payload().trim_matches('"');
-        println!("[Tray] frontend language changed to: {code}");
-        update_lang_checks(&lang_items_for_listen, code);
-    });
+    icon::init(app);
+    let handle = app.handle().clone();
+    app.listen(
+        "mic-state-changed",
+        move |event| match serde_json::from_str::<MicState>(event.payload()) {
+            Ok(mic) => {
+                icon::show(&handle, &mic);
+                session::update(&sessions, &mic);
+            }
+            Err(e) => eprintln!("[Tray] unexpected mic-state-changed payload: {e}"),
+        },
+    );
 
     println!("[Tray] system tray created");
     Ok(())
@@ -0,0 +1,48 @@
+//! Tray item showing whether the backend is up, which offers to start it
+//! again once it has failed.
+
+use tauri::menu::MenuItem;
+use tauri::{App, AppHandle, Listener, Wry};
+
+use crate::backend::{self, BackendStatus, Status};
+
+pub const ID: &str = "backend-status";
+
+fn label(status: Status) -> &'static str {
+    match status {
+        Status::Starting => "Backend starting…",
+        Status::Ready => "Backend ready",
+        Status::Restarting => "Backend restarting…",
+        Status::Failed => "Backend failed — restart",
+    }
+}
+
+fn show(item: &MenuItem<Wry>, status: Status) {
+    let _ = item.set_text(label(status));
+    // Only a failed backend has something to click for
+    let _ = item.set_enabled(status == Status::Failed);
+}
+
+pub fn create(app: &App) -> tauri::Result<MenuItem<Wry>> {
+    let status = backend::status().status;
+    let item = MenuItem::with_id(
+        app,
+        ID,
+        label(status),
+        status == Status::Failed,
+        None::<&str>,
+    )?;
+
+    let listener_item = item.clone();
+    app.listen("backend:status", move |event| {
+        match serde_json::from_str::<BackendStatus>(event.payload()) {
+            Ok(payload) => show(&listener_item, payload.status),
+            Err(e) => eprintln!("[Tray] unexpected backend:status payload: {e}"),
+        }
+    });
+    Ok(item)
+}
+
+pub fn on_click(app: &AppHandle) {
+    backend::restart(app);
+}
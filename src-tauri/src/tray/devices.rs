@@ -0,0 +1,212 @@
+//! The Microphone submenu: the input devices the backend can record from,
+//! checked according to `audio.device`. The backend rescans its devices
+//! when the tray menu opens, or on Linux when a sound device comes or goes,
+//! so a headset plugged in since shows up. Devices are stored by name, as
+//! their indices shift when the list changes.
+
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use std::thread;
+use tauri::menu::{CheckMenuItem, MenuItem, Submenu};
+use tauri::{App, AppHandle, Emitter, Listener, Manager, Wry};
+
+use super::{check_only, CheckItems};
+use crate::backend;
+
+/// Menu ids are `device:<audio.device value>`.
+const ID_PREFIX: &str = "device:";
+
+/// `audio.device` value for the system default input.
+const DEFAULT_DEVICE: &str = "default";
+
+/// libappindicator reports no clicks on the tray icon, so on Linux the
+/// sound device nodes are watched instead, and the backend asked to rescan
+/// only when they change.
+#[cfg(target_os = "linux")]
+const DEVICE_POLL: std::time::Duration = std::time::Duration::from_secs(3);
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct Device {
+    index: u32,
+    name: String,
+}
+
+/// Response of `GET /api/audio/devices`.
+#[derive(Debug, Deserialize)]
+struct Devices {
+    devices: Vec<Device>,
+}
+
+/// The part of `GET /api/config` this menu reflects.
+#[derive(Debug, Deserialize)]
+struct Config {
+    audio: Audio,
+}
+
+#[derive(Debug, Deserialize)]
+struct Audio {
+    device: String,
+}
+
+struct DeviceMenu {
+    submenu: Submenu<Wry>,
+    items: CheckItems,
+    /// What the menu currently lists, to skip rebuilding an unchanged list.
+    devices: Mutex<Option<Vec<Device>>>,
+}
+
+/// A fetch is running (and retrying until the backend answers).
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+fn rebuild(app: &AppHandle, devices: Vec<Device>) -> tauri::Result<()> {
+    let menu = app.state::<DeviceMenu>();
+    if menu
+        .devices
+        .lock()
+        .map_or(true, |listed| listed.as_ref() == Some(&devices))
+    {
+        return Ok(());
+    }
+
+    // No lock is held across the menu calls: they wait for the main thread,
+    // which locks `items` when a device is clicked
+    while menu.submenu.remove_at(0)?.is_some() {}
+    let mut built = Vec::new();
+    let entries = [(DEFAULT_DEVICE, "Default")]
+        .into_iter()
+        .chain(devices.iter().map(|d| (d.name.as_str(), d.name.as_str())));
+    for (value, label) in entries {
+        let id = format!("{ID_PREFIX}{value}");
+        let item = CheckMenuItem::with_id(app, id, label, true, false, None::<&str>)?;
+        menu.submenu.append(&item)?;
+        built.push((value.to_string(), item));
+    }
+    println!("[Tray] input devices: {}", devices.len());
+    if let Ok(mut items) = menu.items.lock() {
+        *items = built;
+    }
+    if let Ok(mut listed) = menu.devices.lock() {
+        *listed = Some(devices);
+    }
+    Ok(())
+}
+
+/// The menu value for `audio.device`: an index, as older configs stored,
+/// is mapped to the name of the device listed at that index.
+fn selected_value(app: &AppHandle, device: String) -> String {
+    let Ok(index) = device.parse::<u32>() else {
+        return device;
+    };
+    let menu = app.state::<DeviceMenu>();
+    let listed = menu.devices.lock().ok();
+    listed
+        .as_ref()
+        .and_then(|listed| listed.as_ref()?.iter().find(|d| d.index == index))
+        .map(|d| d.name.clone())
+        .unwrap_or(device)
+}
+
+/// List the devices in the background (retrying until the backend is up),
+/// then check the configured one.
+fn load(app: &AppHandle, rescan: bool) {
+    let app = app.clone();
+    let path = format!("/api/audio/devices?rescan={rescan}");
+    backend::get_until_ready(path, &LOADING, move |response: Devices| {
+        if let Err(e) = rebuild(&app, response.devices) {
+            eprintln!("[Tray] failed to build input device menu: {e}");
+            return;
+        }
+        match backend::get::<Config>("/api/config") {
+            Ok(config) => {
+                let selected = selected_value(&app, config.audio.device);
+                check_only(&app.state::<DeviceMenu>().items, &selected);
+            }
+            Err(e) => eprintln!("[Tray] failed to read the input device: {e}"),
+        }
+    });
+}
+
+/// Re-read the devices the backend knows and the configured one.
+pub fn refresh(app: &AppHandle) {
+    load(app, false);
+}
+
+/// Have the backend look for devices plugged in since its last scan.
+pub fn rescan(app: &AppHandle) {
+    load(app, true);
+}
+
+/// Sound device nodes, which change when a device is plugged in or out.
+#[cfg(target_os = "linux")]
+fn sound_nodes() -> Vec<std::ffi::OsString> {
+    let mut nodes: Vec<_> = std::fs::read_dir("/dev/snd")
+        .map(|entries| entries.flatten().map(|e| e.file_name()).collect())
+        .unwrap_or_default();
+    nodes.sort();
+    nodes
+}
+
+pub fn create(app: &App) -> tauri::Result<Submenu<Wry>> {
+    let waiting = MenuItem::with_id(
+        app,
+        "device-waiting",
+        "Waiting for backend…",
+        false,
+        None::<&str>,
+    )?;
+    let submenu = Submenu::with_id_and_items(app, "device", "Microphone", true, &[&waiting])?;
+    app.manage(DeviceMenu {
+        submenu: submenu.clone(),
+        items: Mutex::new(Vec::new()),
+        devices: Mutex::new(None),
+    });
+
+    let handle = app.handle().clone();
+    app.listen("config-changed", move |_| refresh(&handle));
+
+    #[cfg(target_os = "linux")]
+    {
+        let handle = app.handle().clone();
+        thread::spawn(move || {
+            let mut nodes = sound_nodes();
+            loop {
+                thread::sleep(DEVICE_POLL);
+                let now = sound_nodes();
+                if now != nodes {
+                    nodes = now;
+                    rescan(&handle);
+                }
+            }
+        });
+    }
+
+    refresh(app.handle());
+    Ok(submenu)
+}
+
+/// Handle a click on an input device; false if `id` is not one.
+pub fn on_menu_event(app: &AppHandle, id: &str) -> bool {
+    let Some(device) = id.strip_prefix(ID_PREFIX) else {
+        return false;
+    };
+    check_only(&app.state::<DeviceMenu>().items, device);
+    println!("[Tray] input device changed to: {device}");
+
+    let app = app.clone();
+    let device = device.to_string();
+    thread::spawn(move || {
+        let patch = json!({ "audio": { "device": device } });
+        match backend::update_config(&patch) {
+            Ok(()) => {
+                let _ = app.emit("config-changed", ());
+            }
+            Err(e) => {
+                eprintln!("[Tray] failed to change input device: {e}");
+                refresh(&app);
+            }
+        }
+    });
+    true
+}
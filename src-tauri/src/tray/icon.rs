@@ -0,0 +1,177 @@
+//! Tray icon and tooltip that follow the microphone state, drawn for a
+//! light or dark panel.
+
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::{App, AppHandle, Manager, Theme, WindowEvent};
+
+use super::{MicMode, MicPhase, MicState, TRAY_ID};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconState {
+    Idle,
+    /// Connecting to the backend or loading the model.
+    Busy,
+    Dictating,
+    Transcribing,
+    Error,
+}
+
+impl MicState {
+    fn icon_state(&self) -> IconState {
+        match (self.state, self.mode) {
+            (MicPhase::Error, _) => IconState::Error,
+            (MicPhase::Connecting | MicPhase::LoadingModel, _) => IconState::Busy,
+            (MicPhase::Recording | MicPhase::Finalizing, MicMode::Dictation) => {
+                IconState::Dictating
+            }
+            (MicPhase::Recording | MicPhase::Finalizing, MicMode::Transcription) => {
+                IconState::Transcribing
+            }
+            _ => IconState::Idle,
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        let language = self.language.to_uppercase();
+        let status = match (self.state, self.mode) {
+            (MicPhase::Error, _) => "Error".to_string(),
+            (MicPhase::Connecting, _) => "Connecting…".to_string(),
+            (MicPhase::LoadingModel, _) => "Loading model…".to_string(),
+            (MicPhase::Recording, MicMode::Dictation) => format!("Dictating ({language})"),
+            (MicPhase::Recording, MicMode::Transcription) => {
+                format!("Transcribing ({language})")
+            }
+            (MicPhase::Finalizing, _) => "Finishing…".to_string(),
+            _ => return "OpenWhisper".to_string(),
+        };
+        format!("OpenWhisper — {status}")
+    }
+}
+
+struct Shown {
+    state: IconState,
+    tooltip: String,
+}
+
+/// What the tray currently shows, so it can be redrawn for a new theme.
+static SHOWN: Mutex<Shown> = Mutex::new(Shown {
+    state: IconState::Idle,
+    tooltip: String::new(),
+});
+
+fn icon_bytes(state: IconState, panel: Theme) -> &'static [u8] {
+    match (panel, state) {
+        (Theme::Dark, IconState::Idle) => include_bytes!("../../icons/tray/dark/idle.png"),
+        (Theme::Dark, IconState::Busy) => include_bytes!("../../icons/tray/dark/busy.png"),
+        (Theme::Dark, IconState::Dictating) => {
+            include_bytes!("../../icons/tray/dark/dictating.png")
+        }
+        (Theme::Dark, IconState::Transcribing) => {
+            include_bytes!("../../icons/tray/dark/transcribing.png")
+        }
+        (Theme::Dark, IconState::Error) => include_bytes!("../../icons/tray/dark/error.png"),
+        (_, IconState::Idle) => include_bytes!("../../icons/tray/light/idle.png"),
+        (_, IconState::Busy) => include_bytes!("../../icons/tray/light/busy.png"),
+        (_, IconState::Dictating) => include_bytes!("../../icons/tray/light/dictating.png"),
+        (_, IconState::Transcribing) => {
+            include_bytes!("../../icons/tray/light/transcribing.png")
+        }
+        (_, IconState::Error) => include_bytes!("../../icons/tray/light/error.png"),
+    }
+}
+
+/// The taskbar has its own light/dark setting, separate from apps'.
+#[cfg(windows)]
+fn panel_theme(_app: &AppHandle) -> Theme {
+    use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let key: Vec<u16> = "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize\0"
+        .encode_utf16()
+        .collect();
+    let value: Vec<u16> = "SystemUsesLightTheme\0".encode_utf16().collect();
+    let mut light = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            (&mut light as *mut u32).cast(),
+            &mut size,
+        )
+    };
+    // Dark is the default taskbar since Windows 10 1903
+    if status == 0 && light != 0 {
+        Theme::Light
+    } else {
+        Theme::Dark
+    }
+}
+
+/// Panels usually follow the desktop theme, which the main window reports.
+/// GNOME's top bar is dark whatever the theme.
+#[cfg(not(windows))]
+fn panel_theme(app: &AppHandle) -> Theme {
+    let gnome = std::env::var("XDG_CURRENT_DESKTOP")
+        .is_ok_and(|desktop| desktop.split(':').any(|d| d.eq_ignore_ascii_case("gnome")));
+    if gnome {
+        return Theme::Dark;
+    }
+    app.get_webview_window("main")
+        .and_then(|window| window.theme().ok())
+        .unwrap_or(Theme::Dark)
+}
+
+/// Redraw the tray from `SHOWN`.
+fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let Ok(shown) = SHOWN.lock() else {
+        return;
+    };
+    match Image::from_bytes(icon_bytes(shown.state, panel_theme(app))) {
+        Ok(icon) => {
+            if let Err(e) = tray.set_icon(Some(icon)) {
+                eprintln!("[Tray] failed to set icon: {e}");
+            }
+        }
+        Err(e) => eprintln!("[Tray] failed to decode icon: {e}"),
+    }
+    let tooltip = Some(shown.tooltip.as_str()).filter(|t| !t.is_empty());
+    if let Err(e) = tray.set_tooltip(tooltip.or(Some("OpenWhisper"))) {
+        eprintln!("[Tray] failed to set tooltip: {e}");
+    }
+}
+
+pub fn show(app: &AppHandle, mic: &MicState) {
+    let (state, tooltip) = (mic.icon_state(), mic.tooltip());
+    {
+        let Ok(mut shown) = SHOWN.lock() else {
+            return;
+        };
+        // The frontend re-sends the state on every language change too
+        if shown.state == state && shown.tooltip == tooltip {
+            return;
+        }
+        println!("[Tray] icon: {state:?}");
+        *shown = Shown { state, tooltip };
+    }
+    refresh(app);
+}
+
+/// Draw the idle icon and follow theme changes from now on.
+pub fn init(app: &App) {
+    if let Some(window) = app.get_webview_window("main") {
+        let handle = app.handle().clone();
+        window.on_window_event(move |event| {
+            if let WindowEvent::ThemeChanged(_) = event {
+                refresh(&handle);
+            }
+        });
+    }
+    refresh(app.handle());
+}
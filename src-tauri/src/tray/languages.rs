@@ -0,0 +1,139 @@
+//! The Language submenu, built from the languages the backend supports once
+//! it is reachable, and rebuilt whenever the settings are saved.
+
+use serde::Deserialize;
+use std::iter;
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItem, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{App, AppHandle, Emitter, Listener, Manager, Wry};
+
+use super::{check_only, CheckItems};
+use crate::backend;
+
+/// Menu ids are `lang:<code>`, to keep them apart from other tray items.
+const ID_PREFIX: &str = "lang:";
+
+#[derive(Debug, Deserialize)]
+struct Language {
+    code: String,
+    name: String,
+}
+
+/// Response of `GET /api/languages`.
+#[derive(Debug, Deserialize)]
+struct Languages {
+    languages: Vec<Language>,
+    /// Code that lets the model detect the language.
+    auto_detect: String,
+    /// `language` from the config.
+    default: String,
+}
+
+struct LanguageMenu {
+    submenu: Submenu<Wry>,
+    items: CheckItems,
+    /// Language the frontend last reported; wins over the configured
+    /// default when the menu is rebuilt.
+    selected: Mutex<Option<String>>,
+}
+
+/// A fetch is running (and retrying until the backend answers).
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+fn rebuild(app: &AppHandle, languages: &Languages) -> tauri::Result<()> {
+    let menu = app.state::<LanguageMenu>();
+    let selected = menu
+        .selected
+        .lock()
+        .ok()
+        .and_then(|s| s.clone())
+        .unwrap_or_else(|| languages.default.clone());
+
+    // No lock is held across the menu calls: they wait for the main thread,
+    // which locks `items` when a language is clicked
+    while menu.submenu.remove_at(0)?.is_some() {}
+    let mut built = Vec::new();
+    let entries = iter::once((&languages.auto_detect, "Auto-detect")).chain(
+        languages
+            .languages
+            .iter()
+            .map(|l| (&l.code, l.name.as_str())),
+    );
+    for (i, (code, name)) in entries.enumerate() {
+        let id = format!("{ID_PREFIX}{code}");
+        let item = CheckMenuItem::with_id(app, id, name, true, *code == selected, None::<&str>)?;
+        menu.submenu.append(&item)?;
+        if i == 0 {
+            menu.submenu.append(&PredefinedMenuItem::separator(app)?)?;
+        }
+        built.push((code.clone(), item));
+    }
+    println!(
+        "[Tray] language menu built: {} languages, {selected} selected",
+        built.len()
+    );
+    if let Ok(mut items) = menu.items.lock() {
+        *items = built;
+    }
+    Ok(())
+}
+
+/// Fetch the languages in the background, retrying until the backend is up.
+fn load(app: &AppHandle) {
+    let app = app.clone();
+    backend::get_until_ready("/api/languages".into(), &LOADING, move |languages| {
+        if let Err(e) = rebuild(&app, &languages) {
+            eprintln!("[Tray] failed to build language menu: {e}");
+        }
+    });
+}
+
+/// The submenu, filled in once the backend answers.
+pub fn create(app: &App) -> tauri::Result<Submenu<Wry>> {
+    let waiting = MenuItem::with_id(
+        app,
+        "lang-waiting",
+        "Waiting for backend…",
+        false,
+        None::<&str>,
+    )?;
+    let submenu = Submenu::with_id_and_items(app, "language", "Language", true, &[&waiting])?;
+    app.manage(LanguageMenu {
+        submenu: submenu.clone(),
+        items: Mutex::new(Vec::new()),
+        selected: Mutex::new(None),
+    });
+
+    // Sync checkmarks with the language picked in the frontend
+    let handle = app.handle().clone();
+    app.listen("language-changed", move |event| {
+        let code = event.payload().trim_matches('"');
+        println!("[Tray] frontend language changed to: {code}");
+        let menu = handle.state::<LanguageMenu>();
+        if let Ok(mut selected) = menu.selected.lock() {
+            *selected = Some(code.to_string());
+        }
+        check_only(&menu.items, code);
+    });
+
+    // The default language may have changed
+    let handle = app.handle().clone();
+    app.listen("config-changed", move |_| load(&handle));
+
+    load(app.handle());
+    Ok(submenu)
+}
+
+/// Handle a click on a language item; false if `id` is not one.
+pub fn on_menu_event(app: &AppHandle, id: &str) -> bool {
+    let Some(code) = id.strip_prefix(ID_PREFIX) else {
+        return false;
+    };
+    // Only the frontend's `language-changed` updates `selected`: it may
+    // refuse the change while a session is running
+    check_only(&app.state::<LanguageMenu>().items, code);
+    println!("[Tray] language changed to: {code}");
+    let _ = app.emit("tray:language-changed", code.to_string());
+    true
+}
@@ -0,0 +1,105 @@
+//! The Model submenu: the Whisper model sizes, checked according to
+//! `models.transcription.model_size`. Picking one saves it to the config,
+//! which the backend applies from the next session on.
+
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use std::thread;
+use tauri::menu::{CheckMenuItem, Submenu};
+use tauri::{App, AppHandle, Emitter, Listener, Manager, Wry};
+
+use super::{check_only, CheckItems};
+use crate::backend;
+
+/// Menu ids are `model:<size>`.
+const ID_PREFIX: &str = "model:";
+
+/// Same choices as the Transcription settings.
+const MODEL_SIZES: &[(&str, &str)] = &[
+    ("auto", "Auto"),
+    ("tiny", "Tiny"),
+    ("base", "Base"),
+    ("small", "Small"),
+    ("medium", "Medium"),
+    ("large-v3-turbo", "Large V3 Turbo"),
+    ("large-v3", "Large V3"),
+];
+
+/// The part of `GET /api/config` this menu reflects.
+#[derive(Debug, Deserialize)]
+struct Config {
+    models: Models,
+}
+
+#[derive(Debug, Deserialize)]
+struct Models {
+    transcription: Transcription,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transcription {
+    model_size: String,
+}
+
+struct ModelMenu {
+    items: CheckItems,
+}
+
+/// A fetch is running (and retrying until the backend answers).
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+/// Check the configured model once the backend answers.
+fn load(app: &AppHandle) {
+    let app = app.clone();
+    backend::get_until_ready("/api/config".into(), &LOADING, move |config: Config| {
+        let size = config.models.transcription.model_size;
+        check_only(&app.state::<ModelMenu>().items, &size);
+    });
+}
+
+pub fn create(app: &App) -> tauri::Result<Submenu<Wry>> {
+    let submenu = Submenu::with_id(app, "model", "Model", true)?;
+    let mut items = Vec::new();
+    for &(size, label) in MODEL_SIZES {
+        let id = format!("{ID_PREFIX}{size}");
+        let item = CheckMenuItem::with_id(app, id, label, true, false, None::<&str>)?;
+        submenu.append(&item)?;
+        items.push((size.to_string(), item));
+    }
+    app.manage(ModelMenu {
+        items: Mutex::new(items),
+    });
+
+    let handle = app.handle().clone();
+    app.listen("config-changed", move |_| load(&handle));
+
+    load(app.handle());
+    Ok(submenu)
+}
+
+/// Handle a click on a model size; false if `id` is not one.
+pub fn on_menu_event(app: &AppHandle, id: &str) -> bool {
+    let Some(size) = id.strip_prefix(ID_PREFIX) else {
+        return false;
+    };
+    check_only(&app.state::<ModelMenu>().items, size);
+    println!("[Tray] model changed to: {size}");
+
+    let app = app.clone();
+    let size = size.to_string();
+    thread::spawn(move || {
+        let patch = json!({ "models": { "transcription": { "model_size": size } } });
+        match backend::update_config(&patch) {
+            Ok(()) => {
+                let _ = app.emit("config-changed", ());
+            }
+            Err(e) => {
+                eprintln!("[Tray] failed to change model: {e}");
+                load(&app);
+            }
+        }
+    });
+    true
+}
@@ -0,0 +1,216 @@
+//! The Recent submenu: the last few sessions, each with actions to copy its
+//! transcript or summary, or open it in the main window. Refreshed whenever
+//! the frontend reports that a session ended.
+
+use serde::Deserialize;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use tauri::menu::{MenuItem, Submenu};
+use tauri::{App, AppHandle, Emitter, Listener, Manager, Wry};
+
+use crate::backend;
+use crate::injection::copy_to_clipboard;
+
+/// Menu ids are `recent:<session id>:<action>`.
+const ID_PREFIX: &str = "recent:";
+
+/// How many sessions the submenu lists.
+const COUNT: usize = 10;
+
+/// A row of `GET /api/sessions`.
+#[derive(Debug, Deserialize)]
+struct SessionSummary {
+    id: i64,
+    /// ISO 8601, local time.
+    started_at: String,
+    /// `None` while the session is still running.
+    duration_s: Option<f64>,
+    summary: Option<String>,
+    /// Name of the uploaded file, for file transcriptions.
+    filename: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sessions {
+    sessions: Vec<SessionSummary>,
+}
+
+/// `GET /api/sessions/{id}`, of which only the text is needed.
+#[derive(Debug, Deserialize)]
+struct SessionDetail {
+    session: SessionSummary,
+    full_text: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    CopyTranscript,
+    CopySummary,
+    Open,
+}
+
+impl Action {
+    const ALL: [Action; 3] = [Action::CopyTranscript, Action::CopySummary, Action::Open];
+
+    fn id(self) -> &'static str {
+        match self {
+            Action::CopyTranscript => "transcript",
+            Action::CopySummary => "summary",
+            Action::Open => "open",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Action::CopyTranscript => "Copy transcript",
+            Action::CopySummary => "Copy summary",
+            Action::Open => "Open",
+        }
+    }
+}
+
+struct RecentMenu {
+    submenu: Submenu<Wry>,
+}
+
+/// A fetch is running (and retrying until the backend answers).
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+/// "3:25", or "1:03:25" past the hour.
+fn duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// The file name for uploads, the start time ("2026-10-16 14:03") for the
+/// rest, followed by the duration.
+fn label(session: &SessionSummary) -> String {
+    let title = match &session.filename {
+        Some(filename) => filename.clone(),
+        None => session
+            .started_at
+            .get(..16)
+            .unwrap_or(&session.started_at)
+            .replace('T', " "),
+    };
+    match session.duration_s {
+        Some(secs) => format!("{title} ({})", duration(secs)),
+        None => title,
+    }
+}
+
+fn placeholder(app: &AppHandle, text: &str) -> tauri::Result<MenuItem<Wry>> {
+    MenuItem::with_id(app, "recent-placeholder", text, false, None::<&str>)
+}
+
+fn rebuild(app: &AppHandle, sessions: &[SessionSummary]) -> tauri::Result<()> {
+    let menu = app.state::<RecentMenu>();
+    while menu.submenu.remove_at(0)?.is_some() {}
+
+    if sessions.is_empty() {
+        return menu.submenu.append(&placeholder(app, "No sessions yet")?);
+    }
+    for session in sessions {
+        let item = Submenu::new(app, label(session), true)?;
+        for action in Action::ALL {
+            let id = format!("{ID_PREFIX}{}:{}", session.id, action.id());
+            let enabled = !matches!(action, Action::CopySummary) || session.summary.is_some();
+            item.append(&MenuItem::with_id(
+                app,
+                id,
+                action.label(),
+                enabled,
+                None::<&str>,
+            )?)?;
+        }
+        menu.submenu.append(&item)?;
+    }
+    println!("[Tray] recent sessions menu built: {}", sessions.len());
+    Ok(())
+}
+
+/// Fetch the latest sessions in the background, retrying until the backend
+/// is up.
+fn load(app: &AppHandle) {
+    let app = app.clone();
+    let path = format!("/api/sessions?limit={COUNT}");
+    backend::get_until_ready(path, &LOADING, move |response: Sessions| {
+        if let Err(e) = rebuild(&app, &response.sessions) {
+            eprintln!("[Tray] failed to build recent sessions menu: {e}");
+        }
+    });
+}
+
+/// The submenu, filled in once the backend answers.
+pub fn create(app: &App) -> tauri::Result<Submenu<Wry>> {
+    let waiting = placeholder(app.handle(), "Waiting for backend…")?;
+    let submenu = Submenu::with_id_and_items(app, "recent", "Recent", true, &[&waiting])?;
+    app.manage(RecentMenu {
+        submenu: submenu.clone(),
+    });
+
+    // Emitted by the frontend when the backend reports `session_ended`
+    let handle = app.handle().clone();
+    app.listen("session-ended", move |_| load(&handle));
+
+    load(app.handle());
+    Ok(submenu)
+}
+
+/// Copy the session's transcript or summary, fetched fresh: the summary is
+/// written in the background after the session ends.
+fn copy(id: i64, action: Action) {
+    thread::spawn(move || {
+        let detail = match backend::get::<SessionDetail>(&format!("/api/sessions/{id}")) {
+            Ok(detail) => detail,
+            Err(e) => {
+                eprintln!("[Tray] failed to fetch session {id}: {}", e.message());
+                return;
+            }
+        };
+        let text = match action {
+            Action::CopySummary => match detail.session.summary {
+                Some(summary) => summary,
+                None => {
+                    eprintln!("[Tray] session {id} has no summary");
+                    return;
+                }
+            },
+            _ => detail.full_text,
+        };
+        match copy_to_clipboard(&text) {
+            Ok(()) => println!("[Tray] copied {} of session {id}", action.id()),
+            Err(e) => eprintln!("[Tray] {}", e.message()),
+        }
+    });
+}
+
+/// Handle a click on a session action; false if `id` is not one.
+pub fn on_menu_event(app: &AppHandle, id: &str) -> bool {
+    let Some((session, action)) = id
+        .strip_prefix(ID_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return false;
+    };
+    let (Ok(session), Some(action)) = (
+        session.parse::<i64>(),
+        Action::ALL.into_iter().find(|a| a.id() == action),
+    ) else {
+        return false;
+    };
+
+    match action {
+        Action::Open => {
+            super::show_main_window(app);
+            let _ = app.emit("tray:open-session", session);
+        }
+        _ => copy(session, action),
+    }
+    true
+}
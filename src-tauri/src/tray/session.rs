@@ -0,0 +1,111 @@
+//! Tray items that start or stop dictation and transcription, labelled with
+//! what is running and for how long, e.g. "Stop transcription (02:14)".
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::menu::MenuItem;
+use tauri::{App, Wry};
+
+use super::{MicMode, MicPhase, MicState};
+
+pub const DICTATION_ID: &str = "toggle-dictation";
+pub const TRANSCRIPTION_ID: &str = "toggle-transcription";
+
+#[derive(Clone)]
+pub struct SessionItems {
+    pub dictation: MenuItem<Wry>,
+    pub transcription: MenuItem<Wry>,
+}
+
+struct Running {
+    /// `MicMode::None` when no session is running.
+    mode: MicMode,
+    /// When the running session started recording.
+    since: Option<Instant>,
+}
+
+static RUNNING: Mutex<Running> = Mutex::new(Running {
+    mode: MicMode::None,
+    since: None,
+});
+
+/// "02:14", or "1:02:14" past the hour.
+fn elapsed(since: Instant) -> String {
+    let secs = since.elapsed().as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m:02}:{s:02}")
+    }
+}
+
+fn label(running: &Running, mode: MicMode, name: &str) -> String {
+    if running.mode != mode {
+        return format!("Start {name}");
+    }
+    match running.since {
+        Some(since) => format!("Stop {name} ({})", elapsed(since)),
+        None => format!("Stop {name}"),
+    }
+}
+
+fn relabel(items: &SessionItems, running: &Running) {
+    for (item, mode, name) in [
+        (&items.dictation, MicMode::Dictation, "dictation"),
+        (
+            &items.transcription,
+            MicMode::Transcription,
+            "transcription",
+        ),
+    ] {
+        if let Err(e) = item.set_text(label(running, mode, name)) {
+            eprintln!("[Tray] failed to relabel {name} item: {e}");
+        }
+    }
+}
+
+pub fn update(items: &SessionItems, mic: &MicState) {
+    let mode = match mic.state {
+        MicPhase::Idle | MicPhase::Error => MicMode::None,
+        _ => mic.mode,
+    };
+    let Ok(mut running) = RUNNING.lock() else {
+        return;
+    };
+    if running.mode != mode {
+        running.mode = mode;
+        running.since = None;
+    }
+    if mic.state == MicPhase::Recording && running.since.is_none() {
+        running.since = Some(Instant::now());
+    }
+    relabel(items, &running);
+}
+
+/// Create the items, and a thread that ticks their durations.
+pub fn create(app: &App) -> tauri::Result<SessionItems> {
+    let items = SessionItems {
+        dictation: MenuItem::with_id(app, DICTATION_ID, "Start dictation", true, None::<&str>)?,
+        transcription: MenuItem::with_id(
+            app,
+            TRANSCRIPTION_ID,
+            "Start transcription",
+            true,
+            None::<&str>,
+        )?,
+    };
+
+    let ticking = items.clone();
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(1));
+        let Ok(running) = RUNNING.lock() else {
+            return;
+        };
+        if running.since.is_some() {
+            relabel(&ticking, &running);
+        }
+    });
+    Ok(items)
+}